@@ -0,0 +1,348 @@
+use std::convert::TryFrom;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum MapError {
+    #[error("Map does not contain any rows")]
+    Empty,
+
+    #[error("Row {0} is empty")]
+    EmptyRow(usize),
+
+    #[error("Row {row} has width {found}, expected {expected}")]
+    InconsistentWidth {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+
+    #[error("Unexpected character {character:?} in row {row}, column {column}")]
+    UnexpectedCharacter {
+        row: usize,
+        column: usize,
+        character: char,
+    },
+
+    #[error("Slope must move downwards")]
+    NonPositiveDown,
+
+    #[error("Fraction with a zero denominator")]
+    ZeroDenominator,
+
+    #[error("Slope is too steep")]
+    Overflow,
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn lcm(a: usize, b: usize) -> Option<usize> {
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+/// A fraction in lowest terms with a positive denominator.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Fraction {
+    numerator: isize,
+    denominator: usize,
+}
+
+impl Fraction {
+    pub fn new(numerator: isize, denominator: usize) -> Result<Fraction, MapError> {
+        if denominator == 0 {
+            return Err(MapError::ZeroDenominator);
+        }
+        let divisor = gcd(numerator.unsigned_abs(), denominator);
+        Ok(Self {
+            numerator: numerator / isize::try_from(divisor).map_err(|_| MapError::Overflow)?,
+            denominator: denominator / divisor,
+        })
+    }
+
+    pub fn numerator(&self) -> isize {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> usize {
+        self.denominator
+    }
+}
+
+impl From<isize> for Fraction {
+    fn from(value: isize) -> Self {
+        Self {
+            numerator: value,
+            denominator: 1,
+        }
+    }
+}
+
+/// A straight line through the map, visiting a cell every `right` columns and `down` rows.
+/// Negative values for `right` move to the left and wrap around like the map does.
+/// Slopes can only be created through [`Slope::new`] and [`Slope::rational`], which make sure
+/// they move downwards.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Slope {
+    right: isize,
+    down: usize,
+    column: usize,
+    row: usize,
+}
+
+impl Slope {
+    pub fn new(right: isize, down: usize) -> Result<Slope, MapError> {
+        if down == 0 {
+            return Err(MapError::NonPositiveDown);
+        }
+        Ok(Self {
+            right,
+            down,
+            column: 0,
+            row: 0,
+        })
+    }
+
+    /// Creates a slope with fractional steps. Since the toboggan can only land on whole cells,
+    /// only the points where the line crosses the integer lattice are visited.
+    pub fn rational(right: Fraction, down: Fraction) -> Result<Slope, MapError> {
+        if down.numerator <= 0 {
+            return Err(MapError::NonPositiveDown);
+        }
+        let steps = lcm(right.denominator, down.denominator).ok_or(MapError::Overflow)?;
+        let right_steps =
+            isize::try_from(steps / right.denominator).map_err(|_| MapError::Overflow)?;
+        Self::new(
+            right
+                .numerator
+                .checked_mul(right_steps)
+                .ok_or(MapError::Overflow)?,
+            (down.numerator as usize)
+                .checked_mul(steps / down.denominator)
+                .ok_or(MapError::Overflow)?,
+        )
+    }
+
+    pub fn right(&self) -> isize {
+        self.right
+    }
+
+    pub fn down(&self) -> usize {
+        self.down
+    }
+
+    /// The column and row of the first cell.
+    pub fn start(&self) -> (usize, usize) {
+        (self.column, self.row)
+    }
+
+    pub fn starting_at(self, column: usize, row: usize) -> Slope {
+        Self {
+            column,
+            row,
+            ..self
+        }
+    }
+}
+
+pub struct Map {
+    rows: Vec<String>,
+}
+
+impl Map {
+    pub fn new(rows: Vec<String>) -> Result<Self, MapError> {
+        let width = match rows.first() {
+            Some(row) => row.len(),
+            None => return Err(MapError::Empty),
+        };
+        for (row, line) in rows.iter().enumerate() {
+            if line.is_empty() {
+                return Err(MapError::EmptyRow(row));
+            }
+            if let Some((column, character)) = line
+                .chars()
+                .enumerate()
+                .find(|&(_, c)| c != '.' && c != '#')
+            {
+                return Err(MapError::UnexpectedCharacter {
+                    row,
+                    column,
+                    character,
+                });
+            }
+            if line.len() != width {
+                return Err(MapError::InconsistentWidth {
+                    row,
+                    expected: width,
+                    found: line.len(),
+                });
+            }
+        }
+        Ok(Self { rows })
+    }
+
+    /// Computed with `i128` so steep slopes far down the map cannot overflow.
+    fn is_tree_in_row(row: &str, y: i128) -> bool {
+        let y = y.rem_euclid(row.len() as i128) as usize;
+        row.as_bytes()[y] == b'#'
+    }
+
+    pub fn count_trees(&self, slope: Slope) -> usize {
+        self.rows
+            .iter()
+            .skip(slope.row)
+            .step_by(slope.down)
+            .enumerate() // Enumerating before skip so we don't have to add 1 to the index later
+            .skip(1)
+            .filter(|(idx, row)| {
+                Self::is_tree_in_row(
+                    row,
+                    slope.column as i128 + *idx as i128 * slope.right as i128,
+                )
+            })
+            .count()
+    }
+
+    pub fn count_all_trees(&self, slopes: Vec<Slope>) -> Vec<usize> {
+        slopes
+            .into_iter()
+            .map(|slope| self.count_trees(slope))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_input() -> Vec<String> {
+        let data = "..##.......
+#...#...#..
+.#....#..#.
+..#.#...#.#
+.#...##..#.
+..#.##.....
+.#.#.#....#
+.#........#
+#.##...#...
+#...##....#
+.#..#...#.#";
+        data.lines().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_part_01() {
+        let map = Map::new(get_input()).unwrap();
+        assert_eq!(map.count_trees(Slope::new(3, 1).unwrap()), 7)
+    }
+
+    #[test]
+    fn test_part_02() {
+        let map = Map::new(get_input()).unwrap();
+        let slopes = vec![(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
+        let slopes = slopes
+            .into_iter()
+            .map(|(right, down)| Slope::new(right, down))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            map.count_all_trees(slopes).into_iter().product::<usize>(),
+            336
+        )
+    }
+
+    fn to_rows(data: &[&str]) -> Vec<String> {
+        data.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_invalid_maps() {
+        assert_eq!(Map::new(vec![]).err(), Some(MapError::Empty));
+        assert_eq!(
+            Map::new(to_rows(&["", ""])).err(),
+            Some(MapError::EmptyRow(0))
+        );
+        assert_eq!(
+            Map::new(to_rows(&["..#", ".#"])).err(),
+            Some(MapError::InconsistentWidth {
+                row: 1,
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(
+            Map::new(to_rows(&["..#", ".X."])).err(),
+            Some(MapError::UnexpectedCharacter {
+                row: 1,
+                column: 1,
+                character: 'X'
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_slope() {
+        assert_eq!(Slope::new(1, 0).err(), Some(MapError::NonPositiveDown));
+        assert_eq!(
+            Slope::rational(1.into(), Fraction::new(-1, 2).unwrap()).err(),
+            Some(MapError::NonPositiveDown)
+        );
+        assert_eq!(Fraction::new(1, 0).err(), Some(MapError::ZeroDenominator));
+        let third = Fraction::new(1, 3).unwrap();
+        assert_eq!((third.numerator(), third.denominator()), (1, 3));
+        let tiny = Fraction::new(1, usize::MAX).unwrap();
+        let other = Fraction::new(1, usize::MAX - 1).unwrap();
+        assert_eq!(Slope::rational(tiny, other).err(), Some(MapError::Overflow));
+        assert_eq!(
+            Slope::rational(isize::MAX.into(), Fraction::new(1, 2).unwrap()).err(),
+            Some(MapError::Overflow)
+        );
+        assert_eq!(
+            Fraction::new(isize::MIN, 1 << 63).err(),
+            Some(MapError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_negative_slope() {
+        let map = Map::new(get_input()).unwrap();
+        let mirrored = get_input()
+            .into_iter()
+            .map(|row| row.chars().rev().collect())
+            .collect();
+        let mirrored = Map::new(mirrored).unwrap();
+        let slope = Slope::new(-3, 1).unwrap().starting_at(10, 0);
+        assert_eq!(mirrored.count_trees(slope), 7);
+        assert_eq!(map.count_trees(Slope::new(-8, 1).unwrap()), 7);
+    }
+
+    #[test]
+    fn test_starting_point() {
+        let map = Map::new(get_input()).unwrap();
+        let shifted = Map::new(get_input().into_iter().skip(2).collect()).unwrap();
+        let slope = Slope::new(3, 1).unwrap().starting_at(0, 2);
+        assert_eq!(
+            map.count_trees(slope),
+            shifted.count_trees(Slope::new(3, 1).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_rational_slope() {
+        let map = Map::new(get_input()).unwrap();
+        let half = Fraction::new(2, 4).unwrap();
+        assert_eq!(half, Fraction::new(1, 2).unwrap());
+        let slope = Slope::rational(half, 1.into()).unwrap();
+        assert_eq!((slope.right(), slope.down()), (1, 2));
+        assert_eq!(map.count_trees(slope), 2);
+        let slope =
+            Slope::rational(Fraction::new(-3, 2).unwrap(), Fraction::new(1, 3).unwrap()).unwrap();
+        assert_eq!((slope.right(), slope.down()), (-9, 2));
+        assert_eq!(slope.start(), (0, 0));
+        assert_eq!(slope.starting_at(3, 1).start(), (3, 1));
+    }
+}
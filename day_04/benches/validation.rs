@@ -0,0 +1,96 @@
+use common::Blocks;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use day_04::schema::{Schema, ValidatorSpec};
+use day_04::{get_passwords, ValidatorSet};
+use regex::Regex;
+
+const PASSPORTS: &str = "\
+eyr:1972 cid:100
+hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926
+
+iyr:2019
+hcl:#602927 eyr:1967 hgt:170cm
+ecl:grn pid:012533040 byr:1946
+
+pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
+hcl:#623a2f
+
+eyr:2029 ecl:blu cid:129 byr:1989
+iyr:2014 pid:896056539 hcl:#a97842 hgt:165cm";
+
+fn large_batch() -> String {
+    vec![PASSPORTS; 250].join("\n\n")
+}
+
+/// How fields were validated before [`ValidatorSet`]: the validator is looked up for every
+/// field, compiling its regex again, and range and height checks compile theirs on each call.
+fn is_valid_per_field(fields: &[(&str, &str)], schema: &Schema) -> bool {
+    fields.iter().all(|&(key, value)| {
+        let spec = match schema.fields.get(key) {
+            Some(field) => &field.validator,
+            None => return false,
+        };
+        match spec {
+            ValidatorSpec::Static { value } => *value,
+            ValidatorSpec::Range { low, high, .. } => {
+                let re = Regex::new(r"\d{4}").unwrap();
+                if !re.is_match(value) {
+                    return false;
+                }
+                match value.parse::<u32>() {
+                    Ok(value) => *low <= value && value <= *high,
+                    Err(_) => false,
+                }
+            }
+            ValidatorSpec::Height { units } => {
+                let names: Vec<_> = units.keys().map(|unit| regex::escape(unit)).collect();
+                let re = Regex::new(&format!(r"(\d+)({})", names.join("|"))).unwrap();
+                let captures = match re.captures(value) {
+                    Some(captures) => captures,
+                    None => return false,
+                };
+                let value: u32 = captures[1].parse().unwrap();
+                let (low, high) = units[&captures[2]];
+                low <= value && value <= high
+            }
+            ValidatorSpec::Regex { pattern } => Regex::new(pattern).unwrap().is_match(value),
+        }
+    })
+}
+
+fn bench_validation(c: &mut Criterion) {
+    let input = large_batch();
+    let passwords = get_passwords(&input);
+    let schema = Schema::default();
+
+    let fields: Vec<Vec<_>> = input
+        .blocks()
+        .map(|block| {
+            block
+                .split_whitespace()
+                .filter_map(|token| token.split_once(':'))
+                .collect()
+        })
+        .collect();
+    c.bench_function("validator per field", |b| {
+        b.iter(|| {
+            fields
+                .iter()
+                .filter(|fields| is_valid_per_field(fields, black_box(&schema)))
+                .count()
+        })
+    });
+
+    let validators = ValidatorSet::new(&schema).unwrap();
+    c.bench_function("shared validator set", |b| {
+        b.iter(|| {
+            passwords
+                .iter()
+                .filter(|p| p.is_valid(black_box(&validators)))
+                .count()
+        })
+    });
+}
+
+criterion_group!(benches, bench_validation);
+criterion_main!(benches);
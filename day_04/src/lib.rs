@@ -0,0 +1,541 @@
+use crate::schema::{Schema, ValidatorSpec};
+use common::Blocks;
+use nom::bytes::complete::tag;
+use nom::combinator::all_consuming;
+use nom::error::{ErrorKind, ParseError};
+use nom::sequence::separated_pair;
+use nom::{AsChar, IResult, InputTakeAtPosition};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use validator::{HeightValidator, RangeValidator, RegexValidator, StaticValidator, Validator};
+
+pub mod passport;
+#[cfg(feature = "records")]
+pub mod records;
+pub mod schema;
+pub mod validator;
+pub mod writer;
+
+#[derive(Copy, Clone)]
+pub struct Entry<'a> {
+    pub key: &'a str,
+    pub value: &'a str,
+}
+
+/// The validators for all fields of a schema, built once and shared by all passports.
+#[derive(Debug)]
+pub struct ValidatorSet {
+    validators: HashMap<String, Box<dyn Validator>>,
+    required: Vec<String>,
+    unknown: StaticValidator,
+}
+
+impl ValidatorSet {
+    /// Fails if a regular expression of the schema does not compile. Schemas loaded through
+    /// [`Schema::load`] have already been checked, but one built by hand might not have been.
+    pub fn new(schema: &Schema) -> Result<Self, regex::Error> {
+        let validators = schema
+            .fields
+            .iter()
+            .map(|(name, field)| {
+                let validator: Box<dyn Validator> = match &field.validator {
+                    ValidatorSpec::Static { value } => StaticValidator::new(*value).boxed(),
+                    ValidatorSpec::Range { low, high, digits } => {
+                        RangeValidator::new(*low, *high, *digits).boxed()
+                    }
+                    ValidatorSpec::Height { units } => HeightValidator::new(units.clone()).boxed(),
+                    ValidatorSpec::Regex { pattern } => RegexValidator::new(pattern)?.boxed(),
+                };
+                Ok((name.clone(), validator))
+            })
+            .collect::<Result<_, _>>()?;
+        let mut required: Vec<_> = schema.required_fields().map(str::to_owned).collect();
+        required.sort();
+        Ok(Self {
+            validators,
+            required,
+            unknown: StaticValidator::new(false),
+        })
+    }
+
+    /// Returns the validator for a field, rejecting every value of fields not in the schema.
+    pub fn get(&self, key: &str) -> &dyn Validator {
+        match self.validators.get(key) {
+            Some(validator) => validator.as_ref(),
+            None => &self.unknown,
+        }
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.validators.contains_key(key)
+    }
+
+    pub fn required_fields(&self) -> impl Iterator<Item = &str> {
+        self.required.iter().map(String::as_str)
+    }
+}
+
+impl Default for ValidatorSet {
+    fn default() -> Self {
+        Self::new(&Schema::default()).unwrap()
+    }
+}
+
+fn key_value(input: &str) -> IResult<&str, Entry<'_>> {
+    separated_pair(allowed_chars, tag(":"), allowed_chars)(input)
+        .map(|(rest, (key, value))| (rest, Entry { key, value }))
+}
+
+pub fn allowed_chars<T, E: ParseError<T>>(input: T) -> IResult<T, T, E>
+where
+    T: InputTakeAtPosition,
+    <T as InputTakeAtPosition>::Item: AsChar,
+{
+    input.split_at_position1_complete(
+        |item| {
+            let char = item.as_char();
+            !(char.is_alphanum() || char == '#')
+        },
+        ErrorKind::AlphaNumeric,
+    )
+}
+
+/// Whether a key or value can be written to a batch and parsed back unchanged.
+pub fn is_token(value: &str) -> bool {
+    all_consuming(allowed_chars::<_, (&str, ErrorKind)>)(value).is_ok()
+}
+
+#[derive(Debug, Serialize)]
+pub struct FieldError<'a> {
+    pub key: &'a str,
+    pub value: &'a str,
+    pub reason: String,
+}
+
+impl<'a> fmt::Display for FieldError<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.key, self.value, self.reason)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValidationReport<'a> {
+    pub missing: Vec<String>,
+    pub unknown: Vec<&'a str>,
+    pub invalid: Vec<FieldError<'a>>,
+}
+
+impl<'a> ValidationReport<'a> {
+    pub fn is_valid(&self) -> bool {
+        self.missing.is_empty() && self.unknown.is_empty() && self.invalid.is_empty()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Password<'a> {
+    fields: HashMap<&'a str, &'a str>,
+}
+
+impl<'a> Password<'a> {
+    pub fn new(entries: Vec<Entry<'a>>) -> Self {
+        let mut map = HashMap::new();
+        for entry in entries {
+            map.insert(entry.key, entry.value);
+        }
+        Self { fields: map }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&'a str> {
+        self.fields.get(key).copied()
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = (&'a str, &'a str)> + '_ {
+        self.fields.iter().map(|(&key, &value)| (key, value))
+    }
+
+    pub fn has_required_fields(&self, validators: &ValidatorSet) -> bool {
+        validators
+            .required_fields()
+            .all(|key| self.fields.contains_key(key))
+    }
+
+    pub fn is_valid(&self, validators: &ValidatorSet) -> bool {
+        self.fields
+            .iter()
+            .all(|(&key, &value)| validators.get(key).is_valid(value))
+    }
+
+    /// Checks every field and collects all problems instead of stopping at the first one.
+    pub fn validate(&self, validators: &ValidatorSet) -> ValidationReport<'a> {
+        let missing = validators
+            .required_fields()
+            .filter(|key| !self.fields.contains_key(key))
+            .map(str::to_owned)
+            .collect();
+        let mut unknown: Vec<_> = self
+            .fields
+            .keys()
+            .copied()
+            .filter(|key| !validators.contains(key))
+            .collect();
+        unknown.sort_unstable();
+        let mut invalid: Vec<_> = self
+            .fields
+            .iter()
+            .filter(|(&key, _)| validators.contains(key))
+            .filter_map(|(&key, &value)| {
+                validators
+                    .get(key)
+                    .validate(value)
+                    .err()
+                    .map(|reason| FieldError { key, value, reason })
+            })
+            .collect();
+        invalid.sort_unstable_by_key(|error| error.key);
+        ValidationReport {
+            missing,
+            unknown,
+            invalid,
+        }
+    }
+}
+
+/// A whitespace separated token of a block that is not a valid `key:value` entry.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MalformedToken<'a> {
+    pub token: &'a str,
+    /// Byte offset of the token within its block.
+    pub offset: usize,
+}
+
+/// Parses a block token by token, skipping malformed tokens instead of failing on them.
+pub fn lenient_password_batch(block: &str) -> (Vec<Entry<'_>>, Vec<MalformedToken<'_>>) {
+    let mut entries = Vec::new();
+    let mut malformed = Vec::new();
+    for token in block.split_whitespace() {
+        match all_consuming(key_value)(token) {
+            Ok((_, entry)) => entries.push(entry),
+            Err(_) => malformed.push(MalformedToken {
+                token,
+                offset: token.as_ptr() as usize - block.as_ptr() as usize,
+            }),
+        }
+    }
+    (entries, malformed)
+}
+
+/// Parses all passports, silently skipping malformed tokens and empty blocks.
+/// Use [`parse_passwords`] to find out about them.
+pub fn get_passwords(input: &str) -> Vec<Password<'_>> {
+    input
+        .blocks()
+        .map(|block| lenient_password_batch(block).0)
+        .filter(|entries| !entries.is_empty())
+        .map(Password::new)
+        .collect()
+}
+
+/// How to treat a questionable entry while parsing.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Policy {
+    /// Report the problem and drop the passport.
+    Error,
+    /// Report the problem but keep the passport.
+    Warn,
+    Ignore,
+}
+
+impl FromStr for Policy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "error" => Ok(Policy::Error),
+            "warn" => Ok(Policy::Warn),
+            "ignore" => Ok(Policy::Ignore),
+            _ => Err(format!("Unknown policy {}", value)),
+        }
+    }
+}
+
+/// The default options keep the same passports as [`get_passwords`]: duplicate keys keep their
+/// last value, unknown keys are left for the validation to reject and malformed tokens are
+/// skipped with a warning.
+#[derive(Copy, Clone, Debug)]
+pub struct ParseOptions {
+    pub duplicate_keys: Policy,
+    pub unknown_keys: Policy,
+    pub malformed_tokens: Policy,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            duplicate_keys: Policy::Ignore,
+            unknown_keys: Policy::Ignore,
+            malformed_tokens: Policy::Warn,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DiagnosticKind<'a> {
+    DuplicateKey {
+        key: &'a str,
+        first: &'a str,
+        second: &'a str,
+    },
+    UnknownKey {
+        key: &'a str,
+    },
+    MalformedToken(MalformedToken<'a>),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Diagnostic<'a> {
+    /// Index of the passport in the batch.
+    pub block: usize,
+    pub policy: Policy,
+    pub kind: DiagnosticKind<'a>,
+}
+
+impl<'a> fmt::Display for Diagnostic<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = match self.policy {
+            Policy::Error => "error",
+            _ => "warning",
+        };
+        write!(f, "{}: passport {}: ", level, self.block)?;
+        match self.kind {
+            DiagnosticKind::DuplicateKey { key, first, second } => {
+                write!(f, "duplicate key {} ({} and {}), ", key, first, second)?;
+                match self.policy {
+                    Policy::Error => write!(f, "passport dropped"),
+                    _ => write!(f, "keeping {}", second),
+                }
+            }
+            DiagnosticKind::UnknownKey { key } => write!(f, "unknown key {}", key),
+            DiagnosticKind::MalformedToken(MalformedToken { token, offset }) => {
+                write!(f, "malformed entry {:?} at offset {}", token, offset)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ParsedBatch<'a> {
+    pub passwords: Vec<Password<'a>>,
+    pub diagnostics: Vec<Diagnostic<'a>>,
+}
+
+/// Parses all passports like [`get_passwords`], but reports duplicate keys, unknown keys and
+/// malformed tokens according to the given options.
+pub fn parse_passwords<'a>(
+    input: &'a str,
+    validators: &ValidatorSet,
+    options: &ParseOptions,
+) -> ParsedBatch<'a> {
+    let mut batch = ParsedBatch::default();
+    for (block, (entries, malformed)) in input.blocks().map(lenient_password_batch).enumerate() {
+        let mut rejected = false;
+        let mut report = |policy: Policy, kind: DiagnosticKind<'a>| {
+            if policy != Policy::Ignore {
+                rejected |= policy == Policy::Error;
+                batch.diagnostics.push(Diagnostic {
+                    block,
+                    policy,
+                    kind,
+                });
+            }
+        };
+        for token in malformed {
+            report(
+                options.malformed_tokens,
+                DiagnosticKind::MalformedToken(token),
+            );
+        }
+        let mut seen: HashMap<&str, &str> = HashMap::new();
+        for entry in &entries {
+            match seen.insert(entry.key, entry.value) {
+                Some(first) => {
+                    let kind = DiagnosticKind::DuplicateKey {
+                        key: entry.key,
+                        first,
+                        second: entry.value,
+                    };
+                    report(options.duplicate_keys, kind);
+                }
+                None if !validators.contains(entry.key) => report(
+                    options.unknown_keys,
+                    DiagnosticKind::UnknownKey { key: entry.key },
+                ),
+                None => {}
+            }
+        }
+        if !rejected && !entries.is_empty() {
+            batch.passwords.push(Password::new(entries));
+        }
+    }
+    batch
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The passports of the part 2 example, four invalid ones followed by four valid ones.
+    pub(crate) fn get_input() -> String {
+        let data = "\
+eyr:1972 cid:100
+hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926
+
+iyr:2019
+hcl:#602927 eyr:1967 hgt:170cm
+ecl:grn pid:012533040 byr:1946
+
+hcl:dab227 iyr:2012
+ecl:brn hgt:182cm pid:021572410 eyr:2020 byr:1992 cid:277
+
+hgt:59cm ecl:zzz
+eyr:2038 hcl:74454a iyr:2023
+pid:3556412378 byr:2007
+
+pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
+hcl:#623a2f
+
+eyr:2029 ecl:blu cid:129 byr:1989
+iyr:2014 pid:896056539 hcl:#a97842 hgt:165cm
+
+hcl:#888785
+hgt:164cm byr:2001 iyr:2015 cid:88
+pid:545766238 ecl:hzl
+eyr:2022
+
+iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719";
+        data.to_owned()
+    }
+
+    #[test]
+    fn test_parse_diagnostics() {
+        let input = "byr:1980 byr:1990 foo:1\n\nbyr:2000";
+        let validators = ValidatorSet::default();
+
+        let batch = parse_passwords(input, &validators, &ParseOptions::default());
+        assert_eq!(batch.passwords.len(), 2);
+        assert_eq!(batch.passwords[0].get("byr"), Some("1990"));
+        assert!(batch.diagnostics.is_empty());
+
+        let options = ParseOptions {
+            duplicate_keys: Policy::Error,
+            unknown_keys: Policy::Warn,
+            ..ParseOptions::default()
+        };
+        let batch = parse_passwords(input, &validators, &options);
+        assert_eq!(batch.passwords.len(), 1);
+        assert_eq!(
+            batch.diagnostics,
+            vec![
+                Diagnostic {
+                    block: 0,
+                    policy: Policy::Error,
+                    kind: DiagnosticKind::DuplicateKey {
+                        key: "byr",
+                        first: "1980",
+                        second: "1990"
+                    }
+                },
+                Diagnostic {
+                    block: 0,
+                    policy: Policy::Warn,
+                    kind: DiagnosticKind::UnknownKey { key: "foo" }
+                }
+            ]
+        );
+        assert_eq!(
+            batch.diagnostics[0].to_string(),
+            "error: passport 0: duplicate key byr (1980 and 1990), passport dropped"
+        );
+        assert_eq!(
+            batch.diagnostics[1].to_string(),
+            "warning: passport 0: unknown key foo"
+        );
+
+        let options = ParseOptions {
+            duplicate_keys: Policy::Warn,
+            ..ParseOptions::default()
+        };
+        let batch = parse_passwords(input, &validators, &options);
+        assert_eq!(
+            batch.diagnostics[0].to_string(),
+            "warning: passport 0: duplicate key byr (1980 and 1990), keeping 1990"
+        );
+    }
+
+    #[test]
+    fn test_malformed_tokens() {
+        let input = "byr:1980 iyr2010 hgt:1-2\npid:1\n\n:x ecl:amb\n\n\n\nnothing";
+        let validators = ValidatorSet::default();
+        let batch = parse_passwords(input, &validators, &ParseOptions::default());
+        assert_eq!(batch.passwords.len(), 2);
+        assert_eq!(batch.passwords[0].get("byr"), Some("1980"));
+        assert_eq!(batch.passwords[0].get("pid"), Some("1"));
+        assert_eq!(batch.passwords[1].get("ecl"), Some("amb"));
+        let diagnostics: Vec<_> = batch.diagnostics.iter().map(|d| d.to_string()).collect();
+        assert_eq!(
+            diagnostics,
+            vec![
+                "warning: passport 0: malformed entry \"iyr2010\" at offset 9",
+                "warning: passport 0: malformed entry \"hgt:1-2\" at offset 17",
+                "warning: passport 1: malformed entry \":x\" at offset 0",
+                "warning: passport 3: malformed entry \"nothing\" at offset 0",
+            ]
+        );
+        assert_eq!(get_passwords(input), batch.passwords);
+
+        let options = ParseOptions {
+            malformed_tokens: Policy::Error,
+            ..ParseOptions::default()
+        };
+        let batch = parse_passwords(input, &validators, &options);
+        assert!(batch.passwords.is_empty());
+    }
+
+    #[test]
+    fn test_report() {
+        let input = get_input();
+        let validators = ValidatorSet::default();
+        let passwords = get_passwords(&input);
+        let reports: Vec<_> = passwords.iter().map(|p| p.validate(&validators)).collect();
+        let first = &reports[0];
+        assert!(first.missing.is_empty());
+        let errors: Vec<_> = first.invalid.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            errors,
+            vec![
+                "eyr 1972 outside 2020..=2030",
+                "hgt 170 missing unit",
+                "pid 186cm does not match ^\\d{9}$"
+            ]
+        );
+
+        let fourth = &reports[3];
+        assert_eq!(
+            fourth.invalid.iter().map(|e| e.key).collect::<Vec<_>>(),
+            vec!["byr", "ecl", "eyr", "hcl", "hgt", "iyr", "pid"]
+        );
+        assert_eq!(
+            fourth.invalid[0].to_string(),
+            "byr 2007 outside 1920..=2002"
+        );
+
+        let passwords = get_passwords("foo:bar byr:2000");
+        let report = passwords[0].validate(&validators);
+        assert_eq!(report.unknown, vec!["foo"]);
+        assert_eq!(
+            report.missing,
+            vec!["ecl", "eyr", "hcl", "hgt", "iyr", "pid"]
+        );
+    }
+}
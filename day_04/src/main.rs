@@ -1,208 +1,191 @@
-use common::{load_data_full, Blocks};
-use nom::bytes::complete::tag;
-use nom::character::complete::multispace0;
-use nom::error::{ErrorKind, ParseError};
-use nom::multi::fold_many1;
-use nom::sequence::{separated_pair, terminated};
-use nom::{AsChar, IResult, InputTakeAtPosition};
-use regex::Regex;
-use std::collections::HashMap;
-use std::fmt::Debug;
-
-fn main() {
-    let input: String = load_data_full("data/day_04.txt");
-    println!("Day 04 Part 1: {}", part_1(&input));
-
-    println!("Day 04 Part 2: {}", part_2(&input));
-}
-
-fn get_validator(key: &str) -> Box<dyn Validator> {
-    match key {
-        "byr" => Box::new(RangeValidator {
-            low: 1920,
-            high: 2002,
-        }),
-        "iyr" => Box::new(RangeValidator {
-            low: 2010,
-            high: 2020,
-        }),
-        "eyr" => Box::new(RangeValidator {
-            low: 2020,
-            high: 2030,
-        }),
-        "hgt" => Box::new(HeightValidator {}),
-        "hcl" => Box::new(RegexValidator {
-            regex: Regex::new(r"^#[0-9a-f]{6}$").unwrap(),
-        }),
-        "ecl" => Box::new(RegexValidator {
-            regex: Regex::new(r"^(amb|blu|brn|gry|grn|hzl|oth)$").unwrap(),
-        }),
-        "pid" => Box::new(RegexValidator {
-            regex: Regex::new(r"^\d{9}$").unwrap(),
-        }),
-        "cid" => Box::new(StaticValidator { value: true }),
-        _ => Box::new(StaticValidator { value: false }),
-    }
-}
-
-fn part_1(input: &str) -> usize {
-    let passwords = get_passwords(&input);
-    passwords
-        .iter()
-        .filter(|&p| p.has_required_fields())
-        .count()
-}
-
-fn part_2(input: &str) -> usize {
-    let passwords = get_passwords(&input);
-    passwords
-        .iter()
-        .filter(|&p| p.has_required_fields())
-        .filter(|&p| p.is_valid())
-        .count()
-}
-
-#[derive(Copy, Clone)]
-struct Entry<'a> {
-    pub key: &'a str,
-    pub value: &'a str,
-}
-
-trait Validator: Debug {
-    fn is_valid(&self, value: &str) -> bool;
-}
-
-#[derive(Debug)]
-struct StaticValidator {
-    value: bool,
-}
-
-impl Validator for StaticValidator {
-    fn is_valid(&self, _: &str) -> bool {
-        return self.value;
-    }
-}
-
-#[derive(Debug)]
-struct RangeValidator {
-    low: u32,
-    high: u32,
-}
-
-impl Validator for RangeValidator {
-    fn is_valid(&self, value: &str) -> bool {
-        let re = Regex::new(r"\d{4}").unwrap();
-        if !re.is_match(value) {
-            return false;
+use anyhow::{anyhow, Result};
+use common::load_data_full;
+use day_04::schema::Schema;
+use day_04::writer::BatchWriter;
+use day_04::{parse_passwords, ParseOptions, Password, ValidationReport, ValidatorSet};
+use serde::Serialize;
+use std::env;
+#[cfg(feature = "records")]
+use {
+    day_04::records::{self, Record},
+    std::fs::File,
+    std::io::{self, BufReader},
+};
+
+const DEFAULT_INPUT: &str = "data/day_04.txt";
+
+/// Usage: `day_04 [--schema <file>] [--duplicate-keys <policy>] [--unknown-keys <policy>]
+/// [--malformed-tokens <policy>]
+/// [report [--json] [<input>] | normalize [--width <n>] [<input>]
+/// | export --format <format> [<input>] | import --format <format> <file>]`
+/// where a policy is one of `error`, `warn` or `ignore` and a format one of `jsonl` or `csv`.
+/// Import and export need the `records` feature.
+fn main() -> Result<()> {
+    let mut args = env::args().skip(1);
+    let mut schema = Schema::default();
+    let mut options = ParseOptions::default();
+    let mut command = Vec::new();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| anyhow!("{} needs a value", arg));
+        match arg.as_str() {
+            "--schema" => schema = Schema::load(value()?)?,
+            "--duplicate-keys" => {
+                options.duplicate_keys = value()?.parse().map_err(|e| anyhow!("{}", e))?
+            }
+            "--malformed-tokens" => {
+                options.malformed_tokens = value()?.parse().map_err(|e| anyhow!("{}", e))?
+            }
+            "--unknown-keys" => {
+                options.unknown_keys = value()?.parse().map_err(|e| anyhow!("{}", e))?
+            }
+            _ => command.push(arg),
         }
-        let value: u32 = match value.parse() {
-            Ok(v) => v,
-            Err(_) => return false,
-        };
-        self.low <= value && value <= self.high
     }
-}
+    let validators = ValidatorSet::new(&schema)?;
 
-#[derive(Debug)]
-struct HeightValidator {}
+    let command: Vec<_> = command.iter().map(String::as_str).collect();
+    match command.as_slice() {
+        [] => {
+            let input: String = load_data_full(DEFAULT_INPUT);
+            let passwords = load_passwords(&input, &validators, &options);
+            println!("Day 04 Part 1: {}", part_1(&passwords, &validators));
 
-impl Validator for HeightValidator {
-    fn is_valid(&self, value: &str) -> bool {
-        let re = Regex::new(r"(\d+)(cm|in)").unwrap();
-        let captures = match re.captures(value) {
-            Some(captures) => captures,
-            None => return false,
-        };
-        let val = captures.get(1).unwrap().as_str().parse().unwrap();
-        let (min, max) = match captures.get(2).unwrap().as_str() {
-            "cm" => (150, 193),
-            _ => (59, 76),
-        };
-        min <= val && val <= max
+            println!("Day 04 Part 2: {}", part_2(&passwords, &validators));
+        }
+        ["report", rest @ ..] => {
+            let (json, file) = match rest {
+                ["--json", file @ ..] => (true, file),
+                file => (false, file),
+            };
+            let input: String = load_data_full(file.first().unwrap_or(&DEFAULT_INPUT));
+            let reports = load_passwords(&input, &validators, &options)
+                .iter()
+                .map(|password| password.validate(&validators))
+                .collect::<Vec<_>>();
+            if json {
+                print_json(&reports)?;
+            } else {
+                print_table(&reports);
+            }
+        }
+        ["normalize", rest @ ..] => {
+            let (writer, file) = match rest {
+                ["--width", width, file @ ..] => (BatchWriter::new(width.parse()?), file),
+                file => (BatchWriter::default(), file),
+            };
+            let input: String = load_data_full(file.first().unwrap_or(&DEFAULT_INPUT));
+            let passwords = load_passwords(&input, &validators, &options);
+            print!("{}", writer.write_batch(&passwords));
+        }
+        #[cfg(feature = "records")]
+        ["export", "--format", format, file @ ..] => {
+            let input: String = load_data_full(file.first().unwrap_or(&DEFAULT_INPUT));
+            let records: Vec<_> = load_passwords(&input, &validators, &options)
+                .iter()
+                .map(|password| Record::new(password, &validators))
+                .collect();
+            let stdout = io::stdout();
+            match *format {
+                "jsonl" => records::write_json_lines(&records, stdout.lock())?,
+                "csv" => records::write_csv(&records, stdout.lock())?,
+                _ => return Err(anyhow!("Unknown format {}", format)),
+            }
+        }
+        #[cfg(feature = "records")]
+        ["import", "--format", format, file] => {
+            let reader = BufReader::new(File::open(file)?);
+            let records = match *format {
+                "jsonl" => records::read_json_lines(reader)?,
+                "csv" => records::read_csv(reader)?,
+                _ => return Err(anyhow!("Unknown format {}", format)),
+            };
+            let passwords = records
+                .iter()
+                .map(Record::password)
+                .collect::<Result<Vec<_>, _>>()?;
+            print!("{}", BatchWriter::default().write_batch(&passwords));
+        }
+        _ => return Err(anyhow!("Unknown command {}", command.join(" "))),
     }
+    Ok(())
 }
 
-#[derive(Debug)]
-struct RegexValidator {
-    regex: Regex,
-}
-
-impl Validator for RegexValidator {
-    fn is_valid(&self, value: &str) -> bool {
-        self.regex.is_match(value)
+fn load_passwords<'a>(
+    input: &'a str,
+    validators: &ValidatorSet,
+    options: &ParseOptions,
+) -> Vec<Password<'a>> {
+    let batch = parse_passwords(input, validators, options);
+    for diagnostic in &batch.diagnostics {
+        eprintln!("{}", diagnostic);
     }
+    batch.passwords
 }
 
-fn key_value(input: &str) -> IResult<&str, Entry> {
-    separated_pair(allowed_chars, tag(":"), allowed_chars)(input)
-        .map(|(rest, (key, value))| (rest, Entry { key, value }))
+#[derive(Serialize)]
+struct ReportEntry<'a> {
+    passport: usize,
+    valid: bool,
+    #[serde(flatten)]
+    report: &'a ValidationReport<'a>,
 }
 
-pub fn allowed_chars<T, E: ParseError<T>>(input: T) -> IResult<T, T, E>
-where
-    T: InputTakeAtPosition,
-    <T as InputTakeAtPosition>::Item: AsChar,
-{
-    input.split_at_position1_complete(
-        |item| {
-            let char = item.as_char();
-            !(char.is_alphanum() || char == '#')
-        },
-        ErrorKind::AlphaNumeric,
-    )
-}
-
-fn password_batch(input: &str) -> IResult<&str, Vec<Entry>> {
-    fold_many1(
-        terminated(key_value, multispace0),
-        Vec::new(),
-        |mut acc, item| {
-            acc.push(item);
-            acc
-        },
-    )(input)
-}
-
-#[derive(Debug)]
-struct Password<'a> {
-    fields: HashMap<&'a str, &'a str>,
+fn print_json(reports: &[ValidationReport]) -> Result<()> {
+    let entries: Vec<_> = reports
+        .iter()
+        .enumerate()
+        .map(|(passport, report)| ReportEntry {
+            passport,
+            valid: report.is_valid(),
+            report,
+        })
+        .collect();
+    println!("{}", serde_json::to_string_pretty(&entries)?);
+    Ok(())
 }
 
-impl<'a> Password<'a> {
-    fn new(entries: Vec<Entry<'a>>) -> Self {
-        let mut map = HashMap::new();
-        for entry in entries {
-            map.insert(entry.key, entry.value);
+fn print_table(reports: &[ValidationReport]) {
+    println!(
+        "{:>8}  {:<5}  {:<12}  problem",
+        "passport", "field", "value"
+    );
+    for (passport, report) in reports.iter().enumerate() {
+        for key in &report.missing {
+            println!("{:>8}  {:<5}  {:<12}  missing", passport, key, "-");
+        }
+        for key in &report.unknown {
+            println!("{:>8}  {:<5}  {:<12}  unknown field", passport, key, "-");
+        }
+        for error in &report.invalid {
+            println!(
+                "{:>8}  {:<5}  {:<12}  {}",
+                passport, error.key, error.value, error.reason
+            );
         }
-        Self { fields: map }
-    }
-
-    fn has_required_fields(&self) -> bool {
-        let expected_keys = vec!["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"];
-
-        expected_keys
-            .into_iter()
-            .all(|key| self.fields.contains_key(key))
     }
+    let valid = reports.iter().filter(|report| report.is_valid()).count();
+    println!("{} of {} passports valid", valid, reports.len());
+}
 
-    fn is_valid(&self) -> bool {
-        self.fields
-            .iter()
-            .all(|(&key, &value)| get_validator(key).is_valid(value))
-    }
+fn part_1(passwords: &[Password], validators: &ValidatorSet) -> usize {
+    passwords
+        .iter()
+        .filter(|&p| p.has_required_fields(validators))
+        .count()
 }
 
-fn get_passwords(input: &str) -> Vec<Password> {
-    input
-        .blocks()
-        .map(|block| password_batch(block).unwrap().1)
-        .map(|entries| Password::new(entries))
-        .collect()
+fn part_2(passwords: &[Password], validators: &ValidatorSet) -> usize {
+    passwords
+        .iter()
+        .filter(|&p| p.has_required_fields(validators))
+        .filter(|&p| p.is_valid(validators))
+        .count()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use day_04::get_passwords;
 
     fn get_input_1() -> String {
         let data = "\
@@ -256,12 +239,38 @@ iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719";
     #[test]
     fn test_part_1() {
         let input = get_input_1();
-        assert_eq!(part_1(&input), 2)
+        assert_eq!(part_1(&get_passwords(&input), &ValidatorSet::default()), 2)
     }
 
     #[test]
     fn test_part_2() {
         let input = get_input_2();
-        assert_eq!(part_2(&input), 4)
+        assert_eq!(part_2(&get_passwords(&input), &ValidatorSet::default()), 4)
+    }
+
+    #[test]
+    fn test_custom_schema() {
+        let schema = Schema::from_json(
+            r#"{
+                "fields": {
+                    "id": { "required": true, "validator": { "type": "regex", "pattern": "^\\d+$" } },
+                    "len": { "validator": { "type": "height", "units": { "m": [1, 5] } } }
+                }
+            }"#,
+        )
+        .unwrap();
+        let input = "id:12 len:3m\n\nid:ab\n\nlen:2m\n\nid:7 len:9m";
+        let validators = ValidatorSet::new(&schema).unwrap();
+        let passwords = get_passwords(input);
+        assert_eq!(part_1(&passwords, &validators), 3);
+        assert_eq!(part_2(&passwords, &validators), 1);
+        assert!(
+            Schema::from_toml("[fields.x]\nvalidator = { type = \"regex\", pattern = \"(\" }")
+                .is_err()
+        );
+        let unchecked: Schema =
+            toml::from_str("[fields.x]\nvalidator = { type = \"regex\", pattern = \"(\" }")
+                .unwrap();
+        assert!(ValidatorSet::new(&unchecked).is_err());
     }
 }
@@ -0,0 +1,401 @@
+use crate::validator::{parse_height, parse_in_range, parse_number};
+use crate::Password;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum PassportError {
+    #[error("missing field {0}")]
+    Missing(&'static str),
+
+    #[error("unknown field {0}")]
+    Unknown(String),
+
+    #[error("{field} {value} {reason}")]
+    Invalid {
+        field: &'static str,
+        value: String,
+        reason: String,
+    },
+}
+
+/// All problems found while converting a [`Password`] into a [`Passport`].
+#[derive(Error, Debug, Clone, PartialEq)]
+pub struct PassportErrors(pub Vec<PassportError>);
+
+impl fmt::Display for PassportErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let errors: Vec<_> = self.0.iter().map(|e| e.to_string()).collect();
+        write!(f, "{}", errors.join(", "))
+    }
+}
+
+macro_rules! year {
+    ($name:ident, $low:expr, $high:expr) => {
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        pub struct $name(pub u32);
+
+        impl FromStr for $name {
+            type Err = String;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                parse_in_range(value, Some(4), $low, $high).map(Self)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+    };
+}
+
+year!(BirthYear, 1920, 2002);
+year!(IssueYear, 2010, 2020);
+year!(ExpirationYear, 2020, 2030);
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum HeightUnit {
+    Cm,
+    In,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Height {
+    pub value: u32,
+    pub unit: HeightUnit,
+}
+
+impl FromStr for Height {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (value, unit) = parse_height(value, |unit| match unit {
+            "cm" => Some((150, 193)),
+            "in" => Some((59, 76)),
+            _ => None,
+        })?;
+        let unit = if unit == "cm" {
+            HeightUnit::Cm
+        } else {
+            HeightUnit::In
+        };
+        Ok(Self { value, unit })
+    }
+}
+
+impl fmt::Display for Height {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = match self.unit {
+            HeightUnit::Cm => "cm",
+            HeightUnit::In => "in",
+        };
+        write!(f, "{}{}", self.value, unit)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HairColor(pub Rgb);
+
+impl FromStr for HairColor {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let hex = match value.strip_prefix('#') {
+            Some(hex) if hex.len() == 6 => hex,
+            _ => return Err("not a color".to_owned()),
+        };
+        if !hex.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f')) {
+            return Err("not a color".to_owned());
+        }
+        let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).unwrap();
+        Ok(Self(Rgb {
+            r: channel(0),
+            g: channel(2),
+            b: channel(4),
+        }))
+    }
+}
+
+impl fmt::Display for HairColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Rgb { r, g, b } = self.0;
+        write!(f, "#{:02x}{:02x}{:02x}", r, g, b)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum EyeColor {
+    Amber,
+    Blue,
+    Brown,
+    Gray,
+    Green,
+    Hazel,
+    Other,
+}
+
+impl FromStr for EyeColor {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        use EyeColor::*;
+        Ok(match value {
+            "amb" => Amber,
+            "blu" => Blue,
+            "brn" => Brown,
+            "gry" => Gray,
+            "grn" => Green,
+            "hzl" => Hazel,
+            "oth" => Other,
+            _ => return Err("not an eye color".to_owned()),
+        })
+    }
+}
+
+impl fmt::Display for EyeColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use EyeColor::*;
+        let code = match self {
+            Amber => "amb",
+            Blue => "blu",
+            Brown => "brn",
+            Gray => "gry",
+            Green => "grn",
+            Hazel => "hzl",
+            Other => "oth",
+        };
+        write!(f, "{}", code)
+    }
+}
+
+/// A passport id keeps its leading zeros, so it is stored as a string of exactly 9 digits.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PassportId(pub String);
+
+impl FromStr for PassportId {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        parse_number(value, Some(9)).map(|_| Self(value.to_owned()))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CountryId(pub String);
+
+impl FromStr for CountryId {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Ok(Self(value.to_owned()))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Passport {
+    pub birth_year: BirthYear,
+    pub issue_year: IssueYear,
+    pub expiration_year: ExpirationYear,
+    pub height: Height,
+    pub hair_color: HairColor,
+    pub eye_color: EyeColor,
+    pub passport_id: PassportId,
+    pub country_id: Option<CountryId>,
+}
+
+impl Passport {
+    /// Returns the fields in the raw `key`, `value` form they were parsed from.
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = vec![
+            ("byr", self.birth_year.to_string()),
+            ("iyr", self.issue_year.to_string()),
+            ("eyr", self.expiration_year.to_string()),
+            ("hgt", self.height.to_string()),
+            ("hcl", self.hair_color.to_string()),
+            ("ecl", self.eye_color.to_string()),
+            ("pid", self.passport_id.0.clone()),
+        ];
+        if let Some(CountryId(country_id)) = &self.country_id {
+            fields.push(("cid", country_id.clone()));
+        }
+        fields
+    }
+}
+
+const FIELDS: [&str; 8] = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid", "cid"];
+
+fn field<T: FromStr<Err = String>>(
+    password: &Password,
+    key: &'static str,
+    errors: &mut Vec<PassportError>,
+) -> Option<T> {
+    let value = password.get(key)?;
+    value
+        .parse()
+        .map_err(|reason| {
+            errors.push(PassportError::Invalid {
+                field: key,
+                value: value.to_owned(),
+                reason,
+            })
+        })
+        .ok()
+}
+
+fn required<T: FromStr<Err = String>>(
+    password: &Password,
+    key: &'static str,
+    errors: &mut Vec<PassportError>,
+) -> Option<T> {
+    if password.get(key).is_none() {
+        errors.push(PassportError::Missing(key));
+    }
+    field(password, key, errors)
+}
+
+impl<'a> TryFrom<Password<'a>> for Passport {
+    type Error = PassportErrors;
+
+    fn try_from(password: Password<'a>) -> Result<Self, Self::Error> {
+        let mut errors = Vec::new();
+        let birth_year = required(&password, "byr", &mut errors);
+        let issue_year = required(&password, "iyr", &mut errors);
+        let expiration_year = required(&password, "eyr", &mut errors);
+        let height = required(&password, "hgt", &mut errors);
+        let hair_color = required(&password, "hcl", &mut errors);
+        let eye_color = required(&password, "ecl", &mut errors);
+        let passport_id = required(&password, "pid", &mut errors);
+        let country_id = field(&password, "cid", &mut errors);
+
+        let mut unknown: Vec<_> = password
+            .fields()
+            .map(|(key, _)| key)
+            .filter(|key| !FIELDS.contains(key))
+            .collect();
+        unknown.sort_unstable();
+        errors.extend(
+            unknown
+                .into_iter()
+                .map(|key| PassportError::Unknown(key.to_owned())),
+        );
+
+        match (
+            birth_year,
+            issue_year,
+            expiration_year,
+            height,
+            hair_color,
+            eye_color,
+            passport_id,
+        ) {
+            (
+                Some(birth_year),
+                Some(issue_year),
+                Some(expiration_year),
+                Some(height),
+                Some(hair_color),
+                Some(eye_color),
+                Some(passport_id),
+            ) if errors.is_empty() => Ok(Passport {
+                birth_year,
+                issue_year,
+                expiration_year,
+                height,
+                hair_color,
+                eye_color,
+                passport_id,
+                country_id,
+            }),
+            _ => Err(PassportErrors(errors)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::get_input;
+    use crate::{get_passwords, ValidatorSet};
+
+    #[test]
+    fn test_valid_passport() {
+        let input = "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980\nhcl:#623a2f";
+        let password = get_passwords(input).remove(0);
+        let passport = Passport::try_from(password).unwrap();
+        assert_eq!(
+            passport,
+            Passport {
+                birth_year: BirthYear(1980),
+                issue_year: IssueYear(2012),
+                expiration_year: ExpirationYear(2030),
+                height: Height {
+                    value: 74,
+                    unit: HeightUnit::In
+                },
+                hair_color: HairColor(Rgb {
+                    r: 0x62,
+                    g: 0x3a,
+                    b: 0x2f
+                }),
+                eye_color: EyeColor::Green,
+                passport_id: PassportId("087499704".to_owned()),
+                country_id: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_all_errors() {
+        let input = "hgt:59cm ecl:zzz\neyr:2038 hcl:74454a iyr:2023\npid:3556412378 foo:bar";
+        let password = get_passwords(input).remove(0);
+        let errors = Passport::try_from(password).unwrap_err();
+        assert_eq!(
+            errors.to_string(),
+            "missing field byr, \
+            iyr 2023 outside 2010..=2020, \
+            eyr 2038 outside 2020..=2030, \
+            hgt 59cm outside 150..=193cm, \
+            hcl 74454a not a color, \
+            ecl zzz not an eye color, \
+            pid 3556412378 not a 9 digit number, \
+            unknown field foo"
+        );
+    }
+
+    #[test]
+    fn test_height() {
+        assert_eq!(
+            "190in".parse::<Height>().unwrap_err(),
+            "outside 59..=76in".to_owned()
+        );
+        assert_eq!(
+            "190".parse::<Height>().unwrap_err(),
+            "missing unit".to_owned()
+        );
+        assert_eq!(
+            "99999999999cm".parse::<Height>().unwrap_err(),
+            "number too large".to_owned()
+        );
+    }
+
+    #[test]
+    fn test_typed_passports() {
+        let input = get_input();
+        let validators = ValidatorSet::default();
+        for password in get_passwords(&input) {
+            let valid = password.validate(&validators).is_valid();
+            assert_eq!(Passport::try_from(password).is_ok(), valid);
+        }
+    }
+}
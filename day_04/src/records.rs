@@ -0,0 +1,259 @@
+use crate::writer::CANONICAL_ORDER;
+use crate::{is_token, Entry, Password, ValidatorSet};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{BufRead, Read, Write};
+use thiserror::Error;
+
+const VALID_COLUMN: &str = "valid";
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum RecordError {
+    #[error("invalid key {0:?}")]
+    InvalidKey(String),
+
+    #[error("invalid value {value:?} for {key}")]
+    InvalidValue { key: String, value: String },
+}
+
+/// An owned passport with its validation status, used to exchange passports with other tools.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Record {
+    #[serde(default)]
+    pub valid: Option<bool>,
+    pub fields: BTreeMap<String, String>,
+}
+
+impl Record {
+    pub fn new(password: &Password, validators: &ValidatorSet) -> Self {
+        Self {
+            valid: Some(password.validate(validators).is_valid()),
+            fields: password
+                .fields()
+                .map(|(key, value)| (key.to_owned(), value.to_owned()))
+                .collect(),
+        }
+    }
+
+    /// Borrows the record as a [`Password`] so it can be validated like a parsed one.
+    /// Keys and values have to be tokens of the batch format, otherwise the passport could
+    /// not be written and read back as the same one.
+    pub fn password(&self) -> Result<Password<'_>, RecordError> {
+        self.fields
+            .iter()
+            .map(|(key, value)| {
+                if !is_token(key) {
+                    Err(RecordError::InvalidKey(key.clone()))
+                } else if !is_token(value) {
+                    Err(RecordError::InvalidValue {
+                        key: key.clone(),
+                        value: value.clone(),
+                    })
+                } else {
+                    Ok(Entry { key, value })
+                }
+            })
+            .collect::<Result<_, _>>()
+            .map(Password::new)
+    }
+}
+
+/// Collects the records, or the errors of all bad rows at once with their line number.
+fn collect_rows<I>(rows: I) -> Result<Vec<Record>>
+where
+    I: Iterator<Item = (u64, Result<Record>)>,
+{
+    let mut records = Vec::new();
+    let mut errors = Vec::new();
+    for (line, row) in rows {
+        let checked = row.and_then(|record| {
+            let tokens = record.password().map(|_| ());
+            tokens.map(|()| record).map_err(anyhow::Error::from)
+        });
+        match checked {
+            Ok(record) => records.push(record),
+            Err(e) => errors.push(format!("Line {}: {}", line, e)),
+        }
+    }
+    if errors.is_empty() {
+        Ok(records)
+    } else {
+        Err(anyhow!(errors.join("\n")))
+    }
+}
+
+pub fn write_json_lines<W: Write>(records: &[Record], mut writer: W) -> Result<()> {
+    for record in records {
+        serde_json::to_writer(&mut writer, record)?;
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+pub fn read_json_lines<R: BufRead>(reader: R) -> Result<Vec<Record>> {
+    collect_rows(
+        reader
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !matches!(line, Ok(line) if line.trim().is_empty()))
+            .map(|(index, line)| {
+                let record = line
+                    .map_err(anyhow::Error::from)
+                    .and_then(|line| Ok(serde_json::from_str(&line)?));
+                (index as u64 + 1, record)
+            }),
+    )
+}
+
+/// Writes one row per record with a `valid` column followed by one column per field.
+/// The puzzle's fields come first, other fields follow alphabetically; missing fields are empty.
+pub fn write_csv<W: Write>(records: &[Record], writer: W) -> Result<()> {
+    let extra: BTreeSet<_> = records
+        .iter()
+        .flat_map(|record| record.fields.keys())
+        .map(String::as_str)
+        .filter(|key| !CANONICAL_ORDER.contains(key))
+        .collect();
+    let columns: Vec<_> = CANONICAL_ORDER.iter().copied().chain(extra).collect();
+
+    let mut writer = csv::Writer::from_writer(writer);
+    writer.write_record(std::iter::once(VALID_COLUMN).chain(columns.iter().copied()))?;
+    for record in records {
+        let valid = match record.valid {
+            Some(valid) => valid.to_string(),
+            None => String::new(),
+        };
+        let fields = columns
+            .iter()
+            .map(|&column| record.fields.get(column).map_or("", String::as_str));
+        writer.write_record(std::iter::once(valid.as_str()).chain(fields))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn read_csv<R: Read>(reader: R) -> Result<Vec<Record>> {
+    let mut reader = csv::Reader::from_reader(reader);
+    let headers = reader.headers()?.clone();
+    let rows = reader.records().enumerate().map(|(index, row)| {
+        // The header is on the first line.
+        let line = row
+            .as_ref()
+            .ok()
+            .and_then(|row| row.position())
+            .map_or(index as u64 + 2, |position| position.line());
+        let record = row.map_err(anyhow::Error::from).and_then(|row| {
+            let mut record = Record::default();
+            for (column, value) in headers.iter().zip(row.iter()) {
+                if column == VALID_COLUMN {
+                    if !value.is_empty() {
+                        record.valid = Some(value.parse()?);
+                    }
+                } else if !value.is_empty() {
+                    record.fields.insert(column.to_owned(), value.to_owned());
+                }
+            }
+            Ok(record)
+        });
+        (line, record)
+    });
+    collect_rows(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_passwords;
+
+    const INPUT: &str = "\
+hcl:#cfa07d eyr:2025 pid:166559648
+iyr:2011 ecl:brn hgt:59in
+
+iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884
+hcl:#cfa07d byr:1929 foo:bar";
+
+    fn get_records() -> Vec<Record> {
+        let validators = ValidatorSet::default();
+        get_passwords(INPUT)
+            .iter()
+            .map(|password| Record::new(password, &validators))
+            .collect()
+    }
+
+    #[test]
+    fn test_json_lines() {
+        let records = get_records();
+        let mut output = Vec::new();
+        write_json_lines(&records, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.lines().count(), 2);
+        assert!(output.starts_with(r##"{"valid":false,"fields":{"ecl":"brn","eyr":"2025""##));
+        assert_eq!(read_json_lines(output.as_bytes()).unwrap(), records);
+    }
+
+    #[test]
+    fn test_csv() {
+        let records = get_records();
+        let mut output = Vec::new();
+        write_csv(&records, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(
+            output.lines().next().unwrap(),
+            "valid,byr,iyr,eyr,hgt,hcl,ecl,pid,cid,foo"
+        );
+        assert_eq!(
+            output.lines().nth(1).unwrap(),
+            "false,,2011,2025,59in,#cfa07d,brn,166559648,,"
+        );
+        assert_eq!(read_csv(output.as_bytes()).unwrap(), records);
+    }
+
+    #[test]
+    fn test_import() {
+        let records = read_csv("byr,pid\n1980,\n".as_bytes()).unwrap();
+        let password = records[0].password().unwrap();
+        assert_eq!(password.get("byr"), Some("1980"));
+        assert_eq!(password.get("pid"), None);
+        assert_eq!(
+            get_passwords(INPUT)[1],
+            get_records()[1].password().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_import_invalid_tokens() {
+        let mut record = Record::default();
+        record.fields.insert("byr".to_owned(), "19 80".to_owned());
+        assert_eq!(
+            record.password().unwrap_err(),
+            RecordError::InvalidValue {
+                key: "byr".to_owned(),
+                value: "19 80".to_owned()
+            }
+        );
+        record.fields.clear();
+        record.fields.insert("pid:x".to_owned(), "1".to_owned());
+        assert_eq!(
+            record.password().unwrap_err(),
+            RecordError::InvalidKey("pid:x".to_owned())
+        );
+
+        let input = "\
+{\"fields\":{\"byr\":\"1980\"}}
+
+{\"fields\":{\"byr\":\"19 80\",\"pid\":\"1\"}}
+{\"fields\":{\"pid:x\":\"1\"}}
+";
+        assert_eq!(
+            read_json_lines(input.as_bytes()).unwrap_err().to_string(),
+            "Line 3: invalid value \"19 80\" for byr\nLine 4: invalid key \"pid:x\""
+        );
+        assert_eq!(
+            read_csv("byr,pid\n1980,1\n1980,\"1 2\"\n".as_bytes())
+                .unwrap_err()
+                .to_string(),
+            "Line 3: invalid value \"1 2\" for pid"
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+const DEFAULT_SCHEMA: &str = include_str!("../schema.toml");
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ValidatorSpec {
+    Static {
+        value: bool,
+    },
+    Range {
+        low: u32,
+        high: u32,
+        digits: Option<usize>,
+    },
+    Height {
+        units: BTreeMap<String, (u32, u32)>,
+    },
+    Regex {
+        pattern: String,
+    },
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct FieldSpec {
+    #[serde(default)]
+    pub required: bool,
+    pub validator: ValidatorSpec,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Schema {
+    pub fields: HashMap<String, FieldSpec>,
+}
+
+impl Default for Schema {
+    fn default() -> Self {
+        Self::from_toml(DEFAULT_SCHEMA).unwrap()
+    }
+}
+
+impl Schema {
+    pub fn from_toml(input: &str) -> Result<Self> {
+        let schema: Schema = toml::from_str(input)?;
+        schema.check()
+    }
+
+    pub fn from_json(input: &str) -> Result<Self> {
+        let schema: Schema = serde_json::from_str(input)?;
+        schema.check()
+    }
+
+    /// Loads a schema from a file, choosing the format based on its extension.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path)?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml(&data),
+            Some("json") => Self::from_json(&data),
+            _ => Err(anyhow!("Unknown schema format {}", path.display())),
+        }
+    }
+
+    /// Makes sure all regular expressions in the schema compile and every height has a unit.
+    fn check(self) -> Result<Self> {
+        for (name, field) in &self.fields {
+            match &field.validator {
+                ValidatorSpec::Regex { pattern } => {
+                    Regex::new(pattern)
+                        .map_err(|e| anyhow!("Invalid pattern for {}: {}", name, e))?;
+                }
+                ValidatorSpec::Height { units } if units.is_empty() => {
+                    return Err(anyhow!("No units given for {}", name));
+                }
+                _ => {}
+            }
+        }
+        Ok(self)
+    }
+
+    pub fn required_fields(&self) -> impl Iterator<Item = &str> {
+        self.fields
+            .iter()
+            .filter(|(_, field)| field.required)
+            .map(|(name, _)| name.as_str())
+    }
+}
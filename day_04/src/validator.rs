@@ -0,0 +1,475 @@
+//! Validators for single field values and combinators to assemble them.
+//!
+//! ```
+//! use day_04::validator::{all_of, length, map, not, one_of, parse_number, range, Validator};
+//!
+//! let year = map(|value| parse_number(value, Some(4))).then(range(1920..=2002));
+//! assert!(year.is_valid("1980"));
+//! assert!(!year.is_valid("2003"));
+//!
+//! let code = all_of(vec![length(3..=3).boxed(), not(one_of(vec!["xxx"])).boxed()]);
+//! assert!(code.is_valid("abc"));
+//! assert!(!code.is_valid("xxx"));
+//! ```
+
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fmt::{Debug, Display};
+use std::marker::PhantomData;
+use std::ops::RangeInclusive;
+
+pub trait Validator<T: ?Sized = str>: Debug {
+    /// Checks a value, returning the reason if it is invalid.
+    fn validate(&self, value: &T) -> Result<(), String>;
+
+    fn is_valid(&self, value: &T) -> bool {
+        self.validate(value).is_ok()
+    }
+
+    fn boxed(self) -> Box<dyn Validator<T>>
+    where
+        Self: Sized + 'static,
+    {
+        Box::new(self)
+    }
+}
+
+impl<T: ?Sized, V: Validator<T> + ?Sized> Validator<T> for Box<V> {
+    fn validate(&self, value: &T) -> Result<(), String> {
+        self.as_ref().validate(value)
+    }
+}
+
+/// Parses a number consisting only of ASCII digits, optionally with an exact number of digits.
+pub fn parse_number(value: &str, digits: Option<usize>) -> Result<u32, String> {
+    if value.is_empty() || !value.chars().all(|c| c.is_ascii_digit()) {
+        return Err("not a number".to_owned());
+    }
+    if let Some(digits) = digits {
+        if value.len() != digits {
+            return Err(format!("not a {} digit number", digits));
+        }
+    }
+    value.parse().map_err(|_| "number too large".to_owned())
+}
+
+pub fn parse_in_range(
+    value: &str,
+    digits: Option<usize>,
+    low: u32,
+    high: u32,
+) -> Result<u32, String> {
+    let number = parse_number(value, digits)?;
+    if low <= number && number <= high {
+        Ok(number)
+    } else {
+        Err(format!("outside {}..={}", low, high))
+    }
+}
+
+/// Parses a number directly followed by a unit and checks it against the range `units` returns
+/// for that unit.
+pub fn parse_height<F>(value: &str, units: F) -> Result<(u32, &str), String>
+where
+    F: Fn(&str) -> Option<(u32, u32)>,
+{
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let (low, high) = match units(unit) {
+        Some(range) => range,
+        None if unit.is_empty() => return Err("missing unit".to_owned()),
+        None => return Err(format!("unknown unit {}", unit)),
+    };
+    let number = parse_number(number, None)?;
+    if low <= number && number <= high {
+        Ok((number, unit))
+    } else {
+        Err(format!("outside {}..={}{}", low, high, unit))
+    }
+}
+
+/// Accepts either every value or none.
+#[derive(Debug)]
+pub struct StaticValidator {
+    value: bool,
+}
+
+impl StaticValidator {
+    pub fn new(value: bool) -> Self {
+        Self { value }
+    }
+}
+
+impl<T: ?Sized> Validator<T> for StaticValidator {
+    fn validate(&self, _: &T) -> Result<(), String> {
+        if self.value {
+            Ok(())
+        } else {
+            Err("not allowed".to_owned())
+        }
+    }
+}
+
+/// Validates a number within an inclusive range, optionally with an exact number of digits.
+#[derive(Debug)]
+pub struct RangeValidator {
+    low: u32,
+    high: u32,
+    digits: Option<usize>,
+}
+
+impl RangeValidator {
+    pub fn new(low: u32, high: u32, digits: Option<usize>) -> Self {
+        Self { low, high, digits }
+    }
+}
+
+impl Validator for RangeValidator {
+    fn validate(&self, value: &str) -> Result<(), String> {
+        parse_in_range(value, self.digits, self.low, self.high).map(|_| ())
+    }
+}
+
+/// Validates a number directly followed by a unit, each unit having its own range.
+#[derive(Debug)]
+pub struct HeightValidator {
+    units: BTreeMap<String, (u32, u32)>,
+}
+
+impl HeightValidator {
+    pub fn new(units: BTreeMap<String, (u32, u32)>) -> Self {
+        Self { units }
+    }
+}
+
+impl Validator for HeightValidator {
+    fn validate(&self, value: &str) -> Result<(), String> {
+        parse_height(value, |unit| self.units.get(unit).copied()).map(|_| ())
+    }
+}
+
+#[derive(Debug)]
+pub struct RegexValidator {
+    regex: Regex,
+}
+
+impl RegexValidator {
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(Self {
+            regex: Regex::new(pattern)?,
+        })
+    }
+}
+
+impl Validator for RegexValidator {
+    fn validate(&self, value: &str) -> Result<(), String> {
+        if self.regex.is_match(value) {
+            Ok(())
+        } else {
+            Err(format!("does not match {}", self.regex))
+        }
+    }
+}
+
+/// See [`all_of`].
+pub struct AllOf<T: ?Sized> {
+    validators: Vec<Box<dyn Validator<T>>>,
+}
+
+impl<T: ?Sized> Debug for AllOf<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AllOf").field(&self.validators).finish()
+    }
+}
+
+/// Accepts a value if every validator does, reporting the first failure.
+pub fn all_of<T: ?Sized>(validators: Vec<Box<dyn Validator<T>>>) -> AllOf<T> {
+    AllOf { validators }
+}
+
+impl<T: ?Sized> Validator<T> for AllOf<T> {
+    fn validate(&self, value: &T) -> Result<(), String> {
+        self.validators
+            .iter()
+            .try_for_each(|validator| validator.validate(value))
+    }
+}
+
+/// See [`any_of`].
+pub struct AnyOf<T: ?Sized> {
+    validators: Vec<Box<dyn Validator<T>>>,
+}
+
+impl<T: ?Sized> Debug for AnyOf<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AnyOf").field(&self.validators).finish()
+    }
+}
+
+/// Accepts a value if at least one validator does, reporting all failures otherwise.
+pub fn any_of<T: ?Sized>(validators: Vec<Box<dyn Validator<T>>>) -> AnyOf<T> {
+    AnyOf { validators }
+}
+
+impl<T: ?Sized> Validator<T> for AnyOf<T> {
+    fn validate(&self, value: &T) -> Result<(), String> {
+        let mut reasons = Vec::new();
+        for validator in &self.validators {
+            match validator.validate(value) {
+                Ok(()) => return Ok(()),
+                Err(reason) => reasons.push(reason),
+            }
+        }
+        Err(reasons.join(" and "))
+    }
+}
+
+/// See [`not`].
+#[derive(Debug)]
+pub struct Not<V> {
+    validator: V,
+}
+
+/// Accepts exactly the values the given validator rejects.
+pub fn not<V>(validator: V) -> Not<V> {
+    Not { validator }
+}
+
+impl<T: ?Sized, V: Validator<T>> Validator<T> for Not<V> {
+    fn validate(&self, value: &T) -> Result<(), String> {
+        match self.validator.validate(value) {
+            Ok(()) => Err("is excluded".to_owned()),
+            Err(_) => Ok(()),
+        }
+    }
+}
+
+/// See [`one_of`].
+#[derive(Debug)]
+pub struct OneOf {
+    values: BTreeSet<String>,
+}
+
+/// Accepts only values from the given set.
+pub fn one_of<I, S>(values: I) -> OneOf
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    OneOf {
+        values: values.into_iter().map(Into::into).collect(),
+    }
+}
+
+impl Validator for OneOf {
+    fn validate(&self, value: &str) -> Result<(), String> {
+        if self.values.contains(value) {
+            Ok(())
+        } else {
+            let values: Vec<_> = self.values.iter().map(String::as_str).collect();
+            Err(format!("not one of {}", values.join(", ")))
+        }
+    }
+}
+
+/// See [`length`].
+#[derive(Debug)]
+pub struct Length {
+    range: RangeInclusive<usize>,
+}
+
+/// Accepts values whose number of characters lies within the range.
+pub fn length(range: RangeInclusive<usize>) -> Length {
+    Length { range }
+}
+
+impl Validator for Length {
+    fn validate(&self, value: &str) -> Result<(), String> {
+        let length = value.chars().count();
+        if self.range.contains(&length) {
+            Ok(())
+        } else {
+            Err(format!(
+                "length {} outside {}..={}",
+                length,
+                self.range.start(),
+                self.range.end()
+            ))
+        }
+    }
+}
+
+/// See [`range`].
+#[derive(Debug)]
+pub struct InRange<T> {
+    range: RangeInclusive<T>,
+}
+
+/// Accepts parsed values within an inclusive range, usually combined with [`map`].
+pub fn range<T>(range: RangeInclusive<T>) -> InRange<T> {
+    InRange { range }
+}
+
+impl<T: PartialOrd + Display + Debug> Validator<T> for InRange<T> {
+    fn validate(&self, value: &T) -> Result<(), String> {
+        if self.range.contains(value) {
+            Ok(())
+        } else {
+            Err(format!(
+                "outside {}..={}",
+                self.range.start(),
+                self.range.end()
+            ))
+        }
+    }
+}
+
+/// A parsing step that still needs a validator for the parsed value, see [`map`].
+pub struct Map<F, T> {
+    parse: F,
+    parsed: PhantomData<fn() -> T>,
+}
+
+/// Parses a value before validating it with the validator given to [`Map::then`].
+pub fn map<F, T>(parse: F) -> Map<F, T>
+where
+    F: Fn(&str) -> Result<T, String>,
+{
+    Map {
+        parse,
+        parsed: PhantomData,
+    }
+}
+
+impl<F, T> Map<F, T>
+where
+    F: Fn(&str) -> Result<T, String>,
+{
+    pub fn then<V: Validator<T>>(self, validator: V) -> Then<F, T, V> {
+        Then {
+            parse: self.parse,
+            validator,
+            parsed: PhantomData,
+        }
+    }
+}
+
+/// See [`Map::then`].
+pub struct Then<F, T, V> {
+    parse: F,
+    validator: V,
+    parsed: PhantomData<fn() -> T>,
+}
+
+impl<F, T, V: Debug> Debug for Then<F, T, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Then")
+            .field("validator", &self.validator)
+            .finish()
+    }
+}
+
+impl<F, T, V> Validator for Then<F, T, V>
+where
+    F: Fn(&str) -> Result<T, String>,
+    V: Validator<T>,
+{
+    fn validate(&self, value: &str) -> Result<(), String> {
+        self.validator.validate(&(self.parse)(value)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validators() {
+        let year = RangeValidator::new(1920, 2002, Some(4));
+        let any_length = RangeValidator::new(0, 100, None);
+        let mut units = BTreeMap::new();
+        units.insert("cm".to_owned(), (150, 193));
+        units.insert("in".to_owned(), (59, 76));
+        let height = HeightValidator::new(units);
+
+        let cases: Vec<(&dyn Validator, &str, Result<(), &str>)> = vec![
+            (&year, "2002", Ok(())),
+            (&year, "1920", Ok(())),
+            (&year, "1919", Err("outside 1920..=2002")),
+            (&year, "2003", Err("outside 1920..=2002")),
+            (&year, "a2000", Err("not a number")),
+            (&year, "2000a", Err("not a number")),
+            (&year, "12000", Err("not a 4 digit number")),
+            (&year, "0200", Err("outside 1920..=2002")),
+            (&year, "+2000", Err("not a number")),
+            (&year, " 2000", Err("not a number")),
+            (&year, "", Err("not a number")),
+            (&any_length, "007", Ok(())),
+            (&any_length, "101", Err("outside 0..=100")),
+            (&any_length, "99999999999999999999", Err("number too large")),
+            (&height, "150cm", Ok(())),
+            (&height, "193cm", Ok(())),
+            (&height, "194cm", Err("outside 150..=193cm")),
+            (&height, "59in", Ok(())),
+            (&height, "77in", Err("outside 59..=76in")),
+            (&height, "190", Err("missing unit")),
+            (&height, "12cmx", Err("unknown unit cmx")),
+            (&height, "x160cm", Err("unknown unit x160cm")),
+            (&height, "cm", Err("not a number")),
+            (&height, "99999999999999999999cm", Err("number too large")),
+        ];
+        for (validator, value, expected) in cases {
+            assert_eq!(
+                validator.validate(value),
+                expected.map_err(str::to_owned),
+                "validating {:?}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_combinators() {
+        let year = map(|value| parse_number(value, Some(4))).then(range(2010..=2020));
+        let color = one_of(vec!["amb", "blu", "brn"]);
+        let code = all_of(vec![
+            length(2..=4).boxed(),
+            not(one_of(vec!["abcd"])).boxed(),
+        ]);
+        let either = any_of(vec![
+            RegexValidator::new("^#").unwrap().boxed(),
+            length(1..=1).boxed(),
+        ]);
+
+        let cases: Vec<(&dyn Validator, &str, Result<(), &str>)> = vec![
+            (&year, "2015", Ok(())),
+            (&year, "2021", Err("outside 2010..=2020")),
+            (&year, "201", Err("not a 4 digit number")),
+            (&color, "blu", Ok(())),
+            (&color, "xyz", Err("not one of amb, blu, brn")),
+            (&code, "ab", Ok(())),
+            (&code, "abcd", Err("is excluded")),
+            (&code, "abcde", Err("length 5 outside 2..=4")),
+            (&code, "äöü", Ok(())),
+            (&either, "#x", Ok(())),
+            (&either, "x", Ok(())),
+            (
+                &either,
+                "xy",
+                Err("does not match ^# and length 2 outside 1..=1"),
+            ),
+        ];
+        for (validator, value, expected) in cases {
+            assert_eq!(
+                validator.validate(value),
+                expected.map_err(str::to_owned),
+                "validating {:?}",
+                value
+            );
+        }
+        assert!(range(1.5..=2.5).is_valid(&2.0));
+    }
+}
@@ -0,0 +1,151 @@
+use crate::passport::Passport;
+use crate::Password;
+
+/// The order in which the puzzle's fields are written. Other fields follow alphabetically.
+pub const CANONICAL_ORDER: [&str; 8] = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid", "cid"];
+
+fn field_order(key: &str) -> (usize, &str) {
+    let position = CANONICAL_ORDER
+        .iter()
+        .position(|&field| field == key)
+        .unwrap_or(CANONICAL_ORDER.len());
+    (position, key)
+}
+
+/// Writes passports in the batch format: `key:value` entries separated by spaces and
+/// newlines, passports separated by blank lines.
+#[derive(Copy, Clone, Debug)]
+pub struct BatchWriter {
+    /// Lines are wrapped before they get longer than this, unless a single entry is longer.
+    pub line_width: usize,
+}
+
+impl Default for BatchWriter {
+    fn default() -> Self {
+        Self { line_width: 60 }
+    }
+}
+
+impl BatchWriter {
+    pub fn new(line_width: usize) -> Self {
+        Self { line_width }
+    }
+
+    pub fn write_entries<'e, I>(&self, entries: I) -> String
+    where
+        I: IntoIterator<Item = (&'e str, &'e str)>,
+    {
+        let mut entries: Vec<_> = entries.into_iter().collect();
+        entries.sort_by_key(|&(key, _)| field_order(key));
+
+        let mut output = String::new();
+        let mut line_length = 0;
+        for (key, value) in entries {
+            let length = key.len() + 1 + value.len();
+            if line_length > 0 {
+                if line_length + 1 + length > self.line_width {
+                    output.push('\n');
+                    line_length = 0;
+                } else {
+                    output.push(' ');
+                    line_length += 1;
+                }
+            }
+            output.push_str(key);
+            output.push(':');
+            output.push_str(value);
+            line_length += length;
+        }
+        output
+    }
+
+    pub fn write_password(&self, password: &Password) -> String {
+        self.write_entries(password.fields())
+    }
+
+    pub fn write_passport(&self, passport: &Passport) -> String {
+        let fields = passport.fields();
+        self.write_entries(fields.iter().map(|(key, value)| (*key, value.as_str())))
+    }
+
+    pub fn write_batch<'p, I>(&self, passwords: I) -> String
+    where
+        I: IntoIterator<Item = &'p Password<'p>>,
+    {
+        let blocks: Vec<_> = passwords
+            .into_iter()
+            .map(|password| self.write_password(password))
+            .collect();
+        blocks.join("\n\n") + "\n"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_passwords, parse_passwords, ParseOptions, Policy, ValidatorSet};
+    use std::convert::TryFrom;
+
+    const INPUT: &str = "\
+hcl:#cfa07d eyr:2025 pid:166559648
+iyr:2011 ecl:brn hgt:59in
+
+zzz:1 cid:350 foo:2 byr:1929";
+
+    #[test]
+    fn test_canonical_order() {
+        let writer = BatchWriter::default();
+        assert_eq!(
+            writer.write_batch(&get_passwords(INPUT)),
+            "\
+iyr:2011 eyr:2025 hgt:59in hcl:#cfa07d ecl:brn pid:166559648
+
+byr:1929 cid:350 foo:2 zzz:1
+"
+        );
+    }
+
+    #[test]
+    fn test_line_wrapping() {
+        let writer = BatchWriter::new(20);
+        let passwords = get_passwords(INPUT);
+        assert_eq!(
+            writer.write_password(&passwords[0]),
+            "iyr:2011 eyr:2025\nhgt:59in hcl:#cfa07d\necl:brn\npid:166559648"
+        );
+        let writer = BatchWriter::new(1);
+        assert_eq!(
+            writer.write_password(&passwords[1]),
+            "byr:1929\ncid:350\nfoo:2\nzzz:1"
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let passwords = get_passwords(INPUT);
+        for width in &[1, 20, 80] {
+            let output = BatchWriter::new(*width).write_batch(&passwords);
+            // Every token has to parse again, the lenient parser would skip broken ones.
+            let options = ParseOptions {
+                malformed_tokens: Policy::Error,
+                ..ParseOptions::default()
+            };
+            let batch = parse_passwords(&output, &ValidatorSet::default(), &options);
+            assert_eq!(batch.diagnostics, vec![]);
+            assert_eq!(batch.passwords, passwords);
+        }
+    }
+
+    #[test]
+    fn test_passport() {
+        let input = "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980\nhcl:#623a2f";
+        let passport = Passport::try_from(get_passwords(input).remove(0)).unwrap();
+        let output = BatchWriter::default().write_passport(&passport);
+        assert_eq!(
+            output,
+            "byr:1980 iyr:2012 eyr:2030 hgt:74in hcl:#623a2f ecl:grn\npid:087499704"
+        );
+        let passport_2 = Passport::try_from(get_passwords(&output).remove(0)).unwrap();
+        assert_eq!(passport, passport_2);
+    }
+}
@@ -0,0 +1,60 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use day_05::finder::{find_seat, find_seat_xor, SeatBitmap};
+use day_05::map::SeatMap;
+use day_05::Layout;
+
+/// A scrambled flight from seat 40 to 900 without seat 500.
+fn get_passes() -> Vec<String> {
+    let layout = Layout::default();
+    (0..861)
+        .map(|i| 40 + i * 577 % 861)
+        .filter(|&id| id != 500)
+        .map(|id| layout.encode(id).unwrap())
+        .collect()
+}
+
+fn sort_and_scan(mut seat_ids: Vec<u32>) -> Option<u32> {
+    seat_ids.sort_unstable();
+    seat_ids
+        .windows(2)
+        .find(|pair| pair[0] + 2 == pair[1])
+        .map(|pair| pair[0] + 1)
+}
+
+fn bench_finders(c: &mut Criterion) {
+    let passes = get_passes();
+    let layout = Layout::default();
+    let seat_ids: Vec<_> = passes
+        .iter()
+        .map(|pass| layout.decode(pass).unwrap().id)
+        .collect();
+
+    c.bench_function("sort and scan", |b| {
+        b.iter(|| sort_and_scan(black_box(&seat_ids).clone()))
+    });
+    c.bench_function("seat map", |b| {
+        b.iter(|| {
+            SeatMap::new(layout, black_box(&passes).iter().map(String::as_str))
+                .unwrap()
+                .find_seat()
+        })
+    });
+    c.bench_function("bitmap", |b| {
+        b.iter(|| {
+            let mut bitmap = SeatBitmap::new();
+            for &id in black_box(&seat_ids) {
+                bitmap.insert(id).unwrap();
+            }
+            bitmap.find_seat()
+        })
+    });
+    c.bench_function("xor", |b| {
+        b.iter(|| find_seat_xor(black_box(&seat_ids).iter().copied()))
+    });
+    c.bench_function("streaming bitmap including decoding", |b| {
+        b.iter(|| find_seat(black_box(&passes)))
+    });
+}
+
+criterion_group!(benches, bench_finders);
+criterion_main!(benches);
@@ -0,0 +1,171 @@
+use crate::{BspError, Layout};
+
+const WORDS: usize = 16;
+
+/// The seats of the default layout that are taken, one bit per seat ID.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SeatBitmap {
+    words: [u64; WORDS],
+}
+
+impl SeatBitmap {
+    pub const SEATS: u32 = (WORDS * 64) as u32;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Marks a seat as taken and returns whether it was free before.
+    pub fn insert(&mut self, seat_id: u32) -> Result<bool, BspError> {
+        if seat_id >= Self::SEATS {
+            return Err(BspError::OutOfRange(seat_id.to_string()));
+        }
+        let (word, bit) = (seat_id as usize / 64, seat_id % 64);
+        let free = self.words[word] >> bit & 1 == 0;
+        self.words[word] |= 1 << bit;
+        Ok(free)
+    }
+
+    pub fn contains(&self, seat_id: u32) -> bool {
+        seat_id < Self::SEATS && self.words[seat_id as usize / 64] >> (seat_id % 64) & 1 == 1
+    }
+
+    /// Finds the only free seat whose neighbours are both taken, checking 64 seats at a time.
+    pub fn find_seat(&self) -> Result<u32, BspError> {
+        let mut candidates = (0..WORDS).flat_map(|index| {
+            let word = self.words[index];
+            let before = if index > 0 { self.words[index - 1] } else { 0 };
+            let after = self.words.get(index + 1).copied().unwrap_or(0);
+            let previous_taken = word << 1 | before >> 63;
+            let next_taken = word >> 1 | after << 63;
+            let mut free = !word & previous_taken & next_taken;
+            std::iter::from_fn(move || {
+                if free == 0 {
+                    return None;
+                }
+                let bit = free.trailing_zeros();
+                free &= free - 1;
+                Some(index as u32 * 64 + bit)
+            })
+        });
+        match (candidates.next(), candidates.next()) {
+            (None, _) => Err(BspError::SeatNotFound),
+            (Some(seat), None) => Ok(seat),
+            (Some(first), Some(second)) => Err(BspError::AmbiguousSeat(
+                vec![first, second].into_iter().chain(candidates).collect(),
+            )),
+        }
+    }
+}
+
+/// Decodes boarding passes of the default layout one by one and finds the free seat between
+/// them without keeping the passes around.
+pub fn find_seat<I, S>(passes: I) -> Result<u32, BspError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let layout = Layout::default();
+    let mut bitmap = SeatBitmap::new();
+    for pass in passes {
+        bitmap.insert(layout.decode(pass.as_ref())?.id)?;
+    }
+    bitmap.find_seat()
+}
+
+/// The XOR of all numbers from 0 to `n`.
+fn xor_up_to(n: u32) -> u32 {
+    match n % 4 {
+        0 => n,
+        1 => 1,
+        2 => n + 1,
+        _ => 0,
+    }
+}
+
+/// Finds the missing seat ID from the lowest, the highest and the XOR of all seat IDs.
+/// This only works if every seat in between is taken exactly once apart from the missing one,
+/// anything else is reported as [`BspError::SeatNotFound`].
+pub fn find_seat_xor<I>(seat_ids: I) -> Result<u32, BspError>
+where
+    I: IntoIterator<Item = u32>,
+{
+    let (mut low, mut high, mut xor, mut count) = (u32::MAX, 0, 0, 0);
+    for id in seat_ids {
+        low = low.min(id);
+        high = high.max(id);
+        xor ^= id;
+        count += 1;
+    }
+    if count < 2 || high - low != count {
+        return Err(BspError::SeatNotFound);
+    }
+    let below = if low == 0 { 0 } else { xor_up_to(low - 1) };
+    let missing = xor ^ xor_up_to(high) ^ below;
+    if missing <= low || missing >= high {
+        return Err(BspError::SeatNotFound);
+    }
+    Ok(missing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_flight(missing: u32) -> Vec<u32> {
+        // A scrambled flight from seat 40 to 900.
+        (0..861)
+            .map(|i| 40 + i * 577 % 861)
+            .filter(|&id| id != missing)
+            .collect()
+    }
+
+    #[test]
+    fn test_bitmap() {
+        let mut bitmap = SeatBitmap::new();
+        for id in get_flight(500) {
+            assert_eq!(bitmap.insert(id), Ok(true));
+        }
+        assert_eq!(bitmap.insert(40), Ok(false));
+        assert!(bitmap.contains(40) && bitmap.contains(900) && !bitmap.contains(500));
+        assert_eq!(bitmap.find_seat(), Ok(500));
+        assert!(bitmap.insert(SeatBitmap::SEATS).is_err());
+    }
+
+    #[test]
+    fn test_word_boundaries() {
+        for &missing in &[63, 64, 127, 128, 1022] {
+            let mut bitmap = SeatBitmap::new();
+            bitmap.insert(missing - 1).unwrap();
+            bitmap.insert(missing + 1).unwrap();
+            assert_eq!(bitmap.find_seat(), Ok(missing));
+        }
+        let mut bitmap = SeatBitmap::new();
+        for &id in &[0, 2, 4, 70] {
+            bitmap.insert(id).unwrap();
+        }
+        assert_eq!(bitmap.find_seat(), Err(BspError::AmbiguousSeat(vec![1, 3])));
+        assert_eq!(SeatBitmap::new().find_seat(), Err(BspError::SeatNotFound));
+    }
+
+    #[test]
+    fn test_streaming() {
+        let layout = Layout::default();
+        let passes = get_flight(777)
+            .into_iter()
+            .map(|id| layout.encode(id).unwrap());
+        assert_eq!(find_seat(passes), Ok(777));
+        assert!(find_seat(vec!["FBFBBFFRL"]).is_err());
+    }
+
+    #[test]
+    fn test_xor() {
+        for &missing in &[41, 500, 899] {
+            assert_eq!(find_seat_xor(get_flight(missing)), Ok(missing));
+        }
+        assert_eq!(find_seat_xor(vec![0, 2]), Ok(1));
+        assert_eq!(find_seat_xor(vec![3, 4, 5]), Err(BspError::SeatNotFound));
+        assert_eq!(find_seat_xor(vec![3, 7]), Err(BspError::SeatNotFound));
+        assert_eq!(find_seat_xor(vec![]), Err(BspError::SeatNotFound));
+    }
+}
@@ -0,0 +1,389 @@
+use nom::character::complete::satisfy;
+use nom::combinator::all_consuming;
+use nom::multi::count;
+use nom::sequence::pair;
+use nom::IResult;
+use std::convert::TryFrom;
+use thiserror::Error;
+
+pub mod finder;
+pub mod map;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum BspError {
+    #[error("Empty boarding pass")]
+    EmptyPass,
+
+    #[error("Boarding pass has only {found} letters, expected {expected}")]
+    TooShort { expected: usize, found: usize },
+
+    #[error("Unexpected {rest:?} after the seat at position {position}")]
+    TrailingInput { position: usize, rest: String },
+
+    #[error("Unexpected character {character:?} at position {position}")]
+    InvalidCharacter { position: usize, character: char },
+
+    /// A column letter among the row letters or the other way around.
+    #[error("Misplaced letter {character:?} at position {position}")]
+    MisplacedLetter { position: usize, character: char },
+
+    #[error("Seat {0} is outside the layout")]
+    OutOfRange(String),
+
+    #[error("No boarding passes")]
+    NoPasses,
+
+    #[error("No empty seat between two taken seats")]
+    SeatNotFound,
+
+    #[error("Several empty seats between two taken seats: {0:?}")]
+    AmbiguousSeat(Vec<u32>),
+
+    #[error("Invalid layout: {0}")]
+    InvalidLayout(String),
+}
+
+/// One dimension of the plane. Each letter of a boarding pass halves the remaining range,
+/// `lower` keeping the lower half and `upper` the upper half.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Axis {
+    lower: char,
+    upper: char,
+    bits: usize,
+}
+
+impl Axis {
+    pub fn new(lower: char, upper: char, bits: usize) -> Self {
+        Self { lower, upper, bits }
+    }
+
+    pub fn lower(&self) -> char {
+        self.lower
+    }
+
+    pub fn upper(&self) -> char {
+        self.upper
+    }
+
+    pub fn bits(&self) -> usize {
+        self.bits
+    }
+
+    pub fn contains(&self, letter: char) -> bool {
+        letter == self.lower || letter == self.upper
+    }
+
+    /// The number of positions along the axis, `None` if they do not fit into a seat ID.
+    pub fn size(&self) -> Option<u32> {
+        u32::try_from(self.bits)
+            .ok()
+            .and_then(|bits| 1u32.checked_shl(bits))
+    }
+
+    fn parser<'a>(&self) -> impl FnMut(&'a str) -> IResult<&'a str, u32> {
+        let axis = *self;
+        let mut letters = count(satisfy(move |c| axis.contains(c)), axis.bits);
+        move |input| {
+            let (rest, letters) = letters(input)?;
+            let value = letters
+                .into_iter()
+                .fold(0, |acc, c| acc << 1 | (c == axis.upper) as u32);
+            Ok((rest, value))
+        }
+    }
+
+    fn encode(&self, value: u32, output: &mut String) {
+        output.extend((0..self.bits).rev().map(|bit| {
+            if value >> bit & 1 == 1 {
+                self.upper
+            } else {
+                self.lower
+            }
+        }));
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Seat {
+    pub row: u32,
+    pub column: u32,
+    pub id: u32,
+}
+
+/// Describes how boarding passes encode seats: the row letters followed by the column letters.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Layout {
+    row: Axis,
+    column: Axis,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            row: Axis::new('F', 'B', 7),
+            column: Axis::new('L', 'R', 3),
+        }
+    }
+}
+
+impl Layout {
+    pub fn new(row: Axis, column: Axis) -> Result<Self, BspError> {
+        let letters = [row.lower, row.upper, column.lower, column.upper];
+        if letters
+            .iter()
+            .enumerate()
+            .any(|(i, c)| letters[i + 1..].contains(c))
+        {
+            return Err(BspError::InvalidLayout(
+                "letters must be distinct".to_owned(),
+            ));
+        }
+        // Checking each axis first keeps the sum from overflowing.
+        let too_wide = row.bits >= 32 || column.bits >= 32 || row.bits + column.bits >= 32;
+        if row.bits == 0 || column.bits == 0 || too_wide {
+            return Err(BspError::InvalidLayout(
+                "both axes need between 1 and 31 bits in total".to_owned(),
+            ));
+        }
+        Ok(Self { row, column })
+    }
+
+    pub fn row(&self) -> Axis {
+        self.row
+    }
+
+    pub fn column(&self) -> Axis {
+        self.column
+    }
+
+    /// The number of rows, which always fits since [`Layout::new`] limits the bits.
+    pub fn rows(&self) -> u32 {
+        1 << self.row.bits
+    }
+
+    pub fn columns(&self) -> u32 {
+        1 << self.column.bits
+    }
+
+    /// The number of letters of a boarding pass.
+    pub fn pass_length(&self) -> usize {
+        self.row.bits + self.column.bits
+    }
+
+    pub fn seat_id(&self, row: u32, column: u32) -> u32 {
+        row * self.columns() + column
+    }
+
+    /// Decodes a boarding pass which has to consist of exactly the row letters followed by the
+    /// column letters.
+    pub fn decode(&self, pass: &str) -> Result<Seat, BspError> {
+        if pass.is_empty() {
+            return Err(BspError::EmptyPass);
+        }
+        let (_, (row, column)) = all_consuming(pair(self.row.parser(), self.column.parser()))(pass)
+            .map_err(|e| {
+                let rest = match e {
+                    nom::Err::Error(e) | nom::Err::Failure(e) => e.input,
+                    nom::Err::Incomplete(_) => "",
+                };
+                self.error(pass, rest)
+            })?;
+        Ok(Seat {
+            row,
+            column,
+            id: self.seat_id(row, column),
+        })
+    }
+
+    /// Explains why decoding `pass` stopped at `rest`.
+    fn error(&self, pass: &str, rest: &str) -> BspError {
+        let position = pass[..pass.len() - rest.len()].chars().count();
+        match rest.chars().next() {
+            None => BspError::TooShort {
+                expected: self.pass_length(),
+                found: position,
+            },
+            Some(_) if position >= self.pass_length() => BspError::TrailingInput {
+                position,
+                rest: rest.to_owned(),
+            },
+            Some(character) => {
+                let other = if position < self.row.bits {
+                    self.column
+                } else {
+                    self.row
+                };
+                if other.contains(character) {
+                    BspError::MisplacedLetter {
+                        position,
+                        character,
+                    }
+                } else {
+                    BspError::InvalidCharacter {
+                        position,
+                        character,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Produces the boarding pass of a seat, the inverse of [`Layout::decode`].
+    pub fn encode_seat(&self, row: u32, column: u32) -> Result<String, BspError> {
+        if row >= self.rows() || column >= self.columns() {
+            return Err(BspError::OutOfRange(format!(
+                "row {} column {}",
+                row, column
+            )));
+        }
+        let mut pass = String::with_capacity(self.pass_length());
+        self.row.encode(row, &mut pass);
+        self.column.encode(column, &mut pass);
+        Ok(pass)
+    }
+
+    pub fn encode(&self, seat_id: u32) -> Result<String, BspError> {
+        if seat_id >= self.rows() * self.columns() {
+            return Err(BspError::OutOfRange(seat_id.to_string()));
+        }
+        self.encode_seat(seat_id / self.columns(), seat_id % self.columns())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        let layout = Layout::default();
+        assert_eq!(
+            layout.decode("FBFBBFFRLR"),
+            Ok(Seat {
+                row: 44,
+                column: 5,
+                id: 357
+            })
+        );
+        assert_eq!(
+            layout.decode("BBFFBBFRLL"),
+            Ok(Seat {
+                row: 102,
+                column: 4,
+                id: 820
+            })
+        );
+    }
+
+    #[test]
+    fn test_custom_layout() {
+        let layout = Layout::new(Axis::new('0', '1', 2), Axis::new('a', 'b', 4)).unwrap();
+        assert_eq!(
+            layout.decode("10baab"),
+            Ok(Seat {
+                row: 2,
+                column: 9,
+                id: 41
+            })
+        );
+        assert!(Layout::new(Axis::new('F', 'B', 7), Axis::new('B', 'R', 3)).is_err());
+        assert!(Layout::new(Axis::new('F', 'B', 30), Axis::new('L', 'R', 3)).is_err());
+        assert!(Layout::new(Axis::new('F', 'B', usize::MAX), Axis::new('L', 'R', 3)).is_err());
+        let wide = Axis::new('L', 'R', 40);
+        assert_eq!((wide.bits(), wide.size()), (40, None));
+        assert_eq!(Axis::new('L', 'R', 3).size(), Some(8));
+    }
+
+    #[test]
+    fn test_encode() {
+        let layout = Layout::default();
+        assert_eq!(layout.encode(357).unwrap(), "FBFBBFFRLR");
+        assert_eq!(layout.encode_seat(102, 4).unwrap(), "BBFFBBFRLL");
+        assert_eq!(
+            layout.encode(1024),
+            Err(BspError::OutOfRange("1024".to_owned()))
+        );
+        assert_eq!(
+            layout.encode_seat(12, 8),
+            Err(BspError::OutOfRange("row 12 column 8".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let layouts = [
+            Layout::default(),
+            Layout::new(Axis::new('0', '1', 2), Axis::new('a', 'b', 4)).unwrap(),
+        ];
+        for layout in &layouts {
+            for id in 0..layout.rows() * layout.columns() {
+                let pass = layout.encode(id).unwrap();
+                let seat = layout.decode(&pass).unwrap();
+                assert_eq!(seat.id, id);
+                assert_eq!(layout.encode_seat(seat.row, seat.column).unwrap(), pass);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_passes() {
+        let layout = Layout::default();
+        assert_eq!(layout.decode(""), Err(BspError::EmptyPass));
+        assert_eq!(
+            layout.decode("FBFBBFFRL"),
+            Err(BspError::TooShort {
+                expected: 10,
+                found: 9
+            })
+        );
+        assert_eq!(
+            layout.decode("FBF"),
+            Err(BspError::TooShort {
+                expected: 10,
+                found: 3
+            })
+        );
+        assert_eq!(
+            layout.decode("FBFBBFFRLRxyz"),
+            Err(BspError::TrailingInput {
+                position: 10,
+                rest: "xyz".to_owned()
+            })
+        );
+        assert_eq!(
+            layout.decode("FBFBBFFRLRL"),
+            Err(BspError::TrailingInput {
+                position: 10,
+                rest: "L".to_owned()
+            })
+        );
+        assert_eq!(
+            layout.decode("FBFXBFFRLR"),
+            Err(BspError::InvalidCharacter {
+                position: 3,
+                character: 'X'
+            })
+        );
+        assert_eq!(
+            layout.decode("FBLBBFFRLR"),
+            Err(BspError::MisplacedLetter {
+                position: 2,
+                character: 'L'
+            })
+        );
+        assert_eq!(
+            layout.decode("FBFBBFFRBR"),
+            Err(BspError::MisplacedLetter {
+                position: 8,
+                character: 'B'
+            })
+        );
+        assert_eq!(
+            layout.decode("RLRFBFBBFF"),
+            Err(BspError::MisplacedLetter {
+                position: 0,
+                character: 'R'
+            })
+        );
+    }
+}
@@ -1,54 +1,75 @@
+use anyhow::{anyhow, Result};
 use common::load_data;
-use nom::bytes::complete::take_while;
-use nom::combinator::map_parser;
-use nom::IResult;
+use day_05::finder;
+use day_05::map::{Neighbour, SeatMap};
+use day_05::{BspError, Layout};
+use std::env;
 
-fn main() {
-    let input: Vec<String> = load_data("data/day_05.txt");
-    println!("Day 05 Part 1: {}", part_1(&input));
-    println!("Day 05 Part 2: {}", part_2(&input));
+const DEFAULT_INPUT: &str = "data/day_05.txt";
+
+/// Usage: `day_05 [map [<input>]]`
+fn main() -> Result<()> {
+    let args: Vec<_> = env::args().skip(1).collect();
+    let args: Vec<_> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        [] => {
+            let input: Vec<String> = load_data(DEFAULT_INPUT);
+            println!("Day 05 Part 1: {}", part_1(&input)?);
+            println!("Day 05 Part 2: {}", part_2(&input)?);
+        }
+        ["map", file @ ..] => {
+            let input: Vec<String> = load_data(file.first().unwrap_or(&DEFAULT_INPUT));
+            let map = seat_map(&input)?;
+            print!("{}", map);
+            for empty in map.empty_seats() {
+                println!(
+                    "empty: row {:>3} column {} id {:>4}  previous {:<8} next {}",
+                    empty.seat.row,
+                    empty.seat.column,
+                    empty.seat.id,
+                    neighbour_name(empty.previous),
+                    neighbour_name(empty.next)
+                );
+            }
+        }
+        _ => return Err(anyhow!("Unknown command {}", args.join(" "))),
+    }
+    Ok(())
 }
 
-fn part_1(input: &Vec<String>) -> u32 {
-    input.iter().map(|line| parse(line)).max().unwrap()
+fn neighbour_name(neighbour: Neighbour) -> &'static str {
+    match neighbour {
+        Neighbour::Occupied => "occupied",
+        Neighbour::Empty => "empty",
+        Neighbour::Edge => "edge",
+    }
 }
 
-fn part_2(input: &Vec<String>) -> u32 {
-    let mut seats: Vec<_> = input.iter().map(|line| parse(line)).collect();
-    seats.sort();
-    seats
-        .windows(2)
-        .filter_map(|input| {
-            if let [first, second] = input {
-                if first + 2 == *second {
-                    return Some(first + 1);
-                };
-            }
-            None
-        })
-        .next()
-        .unwrap()
+/// Builds the seat map of the default layout and warns about duplicate boarding passes.
+fn seat_map(input: &[String]) -> Result<SeatMap, BspError> {
+    let map = SeatMap::new(Layout::default(), input.iter().map(String::as_str))?;
+    for duplicate in map.duplicates() {
+        eprintln!(
+            "warning: lines {} and {} both have seat {}",
+            duplicate.first + 1,
+            duplicate.second + 1,
+            duplicate.seat.id
+        );
+    }
+    Ok(map)
 }
 
-fn parser(input: &str) -> IResult<&str, u32> {
-    map_parser(
-        take_while(move |c| c == 'F' || c == 'B' || c == 'L' || c == 'R'),
-        move |input: &str| {
-            let exp = input.len() - 1;
-            let res = input.char_indices().fold(0, |acc, (index, char)| {
-                if char == 'B' || char == 'R' {
-                    acc + (1 << (exp - index))
-                } else {
-                    acc
-                }
-            });
-            Ok(("", res))
-        },
-    )(input)
+fn part_1(input: &[String]) -> Result<u32, BspError> {
+    let layout = Layout::default();
+    let seat_ids = input
+        .iter()
+        .map(|line| layout.decode(line).map(|seat| seat.id))
+        .collect::<Result<Vec<_>, _>>()?;
+    seat_ids.into_iter().max().ok_or(BspError::NoPasses)
 }
 
-fn parse(input: &str) -> u32 {
-    parser(input).unwrap().1
+fn part_2(input: &[String]) -> Result<u32, BspError> {
+    finder::find_seat(input)
 }
 
 #[cfg(test)]
@@ -67,6 +88,15 @@ BBFFBBFRLL";
     #[test]
     fn test_part_1() {
         let input = get_input();
-        assert_eq!(part_1(&input), 820)
+        assert_eq!(part_1(&input).unwrap(), 820);
+        assert_eq!(part_1(&[]), Err(BspError::NoPasses));
+    }
+
+    #[test]
+    fn test_part_2() {
+        let mut input = get_input();
+        assert_eq!(part_2(&input), Err(BspError::SeatNotFound));
+        input.push("BBFFBBFRRL".to_owned());
+        assert_eq!(part_2(&input).unwrap(), 821);
     }
 }
@@ -0,0 +1,186 @@
+use crate::{BspError, Layout, Seat};
+use std::fmt;
+
+/// Two boarding passes for the same seat, given by their index in the input.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Duplicate {
+    pub seat: Seat,
+    pub first: usize,
+    pub second: usize,
+}
+
+/// The status of the seat next to an empty seat, by seat ID.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Neighbour {
+    Occupied,
+    Empty,
+    /// The empty seat is the first or last seat of the plane.
+    Edge,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EmptySeat {
+    pub seat: Seat,
+    pub previous: Neighbour,
+    pub next: Neighbour,
+}
+
+/// Which seats of a plane are taken, built from all boarding passes of a flight.
+#[derive(Clone, Debug)]
+pub struct SeatMap {
+    layout: Layout,
+    /// The index of the first boarding pass for each seat ID.
+    seats: Vec<Option<usize>>,
+    duplicates: Vec<Duplicate>,
+}
+
+impl SeatMap {
+    pub fn new<'a, I>(layout: Layout, passes: I) -> Result<Self, BspError>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut map = Self {
+            layout,
+            seats: vec![None; (layout.rows() * layout.columns()) as usize],
+            duplicates: Vec::new(),
+        };
+        for (index, pass) in passes.into_iter().enumerate() {
+            let seat = layout.decode(pass)?;
+            match map.seats[seat.id as usize] {
+                Some(first) => map.duplicates.push(Duplicate {
+                    seat,
+                    first,
+                    second: index,
+                }),
+                None => map.seats[seat.id as usize] = Some(index),
+            }
+        }
+        Ok(map)
+    }
+
+    pub fn is_occupied(&self, seat_id: u32) -> bool {
+        matches!(self.seats.get(seat_id as usize), Some(Some(_)))
+    }
+
+    pub fn duplicates(&self) -> &[Duplicate] {
+        &self.duplicates
+    }
+
+    pub fn occupied_seats(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..self.seats.len() as u32).filter(move |&id| self.is_occupied(id))
+    }
+
+    fn neighbour(&self, seat_id: Option<u32>) -> Neighbour {
+        match seat_id {
+            Some(id) if (id as usize) < self.seats.len() => {
+                if self.is_occupied(id) {
+                    Neighbour::Occupied
+                } else {
+                    Neighbour::Empty
+                }
+            }
+            _ => Neighbour::Edge,
+        }
+    }
+
+    pub fn empty_seats(&self) -> Vec<EmptySeat> {
+        let columns = self.layout.columns();
+        (0..self.seats.len() as u32)
+            .filter(|&id| !self.is_occupied(id))
+            .map(|id| EmptySeat {
+                seat: Seat {
+                    row: id / columns,
+                    column: id % columns,
+                    id,
+                },
+                previous: self.neighbour(id.checked_sub(1)),
+                next: self.neighbour(Some(id + 1)),
+            })
+            .collect()
+    }
+
+    /// Finds the only empty seat whose neighbours are both taken.
+    pub fn find_seat(&self) -> Result<Seat, BspError> {
+        let candidates: Vec<_> = self
+            .empty_seats()
+            .into_iter()
+            .filter(|empty| {
+                empty.previous == Neighbour::Occupied && empty.next == Neighbour::Occupied
+            })
+            .map(|empty| empty.seat)
+            .collect();
+        match candidates.as_slice() {
+            [] => Err(BspError::SeatNotFound),
+            [seat] => Ok(*seat),
+            _ => Err(BspError::AmbiguousSeat(
+                candidates.iter().map(|seat| seat.id).collect(),
+            )),
+        }
+    }
+}
+
+/// Renders one line per row with `#` for taken and `.` for empty seats.
+impl fmt::Display for SeatMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.seats.chunks(self.layout.columns() as usize) {
+            let line: String = row
+                .iter()
+                .map(|seat| if seat.is_some() { '#' } else { '.' })
+                .collect();
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Axis;
+
+    fn get_layout() -> Layout {
+        Layout::new(Axis::new('F', 'B', 2), Axis::new('L', 'R', 2)).unwrap()
+    }
+
+    #[test]
+    fn test_render() {
+        let map = SeatMap::new(get_layout(), vec!["FFLL", "FBRR", "BBLR", "FBRR"]).unwrap();
+        assert_eq!(map.to_string(), "#...\n...#\n....\n.#..\n");
+        assert_eq!(map.occupied_seats().collect::<Vec<_>>(), vec![0, 7, 13]);
+        assert_eq!(
+            map.duplicates(),
+            &[Duplicate {
+                seat: Seat {
+                    row: 1,
+                    column: 3,
+                    id: 7
+                },
+                first: 1,
+                second: 3
+            }]
+        );
+    }
+
+    #[test]
+    fn test_empty_seats() {
+        let map = SeatMap::new(get_layout(), vec!["FFLR", "FFRR", "BBRL"]).unwrap();
+        let empty = map.empty_seats();
+        assert_eq!(empty.len(), 13);
+        assert_eq!(empty[0].previous, Neighbour::Edge);
+        assert_eq!(empty[0].next, Neighbour::Occupied);
+        assert_eq!(empty[1].previous, Neighbour::Occupied);
+        assert_eq!(empty[1].next, Neighbour::Occupied);
+        assert_eq!(empty[12].previous, Neighbour::Occupied);
+        assert_eq!(empty[12].next, Neighbour::Edge);
+        assert_eq!(map.find_seat().unwrap().id, 2);
+    }
+
+    #[test]
+    fn test_find_seat_errors() {
+        let map = SeatMap::new(get_layout(), vec!["FFLL", "FBLL"]).unwrap();
+        assert_eq!(map.find_seat(), Err(BspError::SeatNotFound));
+        let map = SeatMap::new(get_layout(), vec!["FFLL", "FFRL", "FBLL"]).unwrap();
+        assert_eq!(map.find_seat(), Err(BspError::AmbiguousSeat(vec![1, 3])));
+        assert!(SeatMap::new(get_layout(), vec!["FFL"]).is_err());
+    }
+}
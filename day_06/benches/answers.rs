@@ -0,0 +1,102 @@
+use common::Blocks;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use day_06::AnswerSet;
+use std::collections::HashSet;
+
+/// Builds groups of one to five people with pseudo random answers.
+fn large_input() -> String {
+    let mut state: u32 = 6;
+    let mut next = move || {
+        state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        state >> 16
+    };
+    let groups: Vec<_> = (0..2000)
+        .map(|_| {
+            let people: Vec<String> = (0..1 + next() % 5)
+                .map(|_| {
+                    let answers = next();
+                    (b'a'..=b'z')
+                        .filter(|letter| answers >> (letter % 15) & 1 == 1)
+                        .map(char::from)
+                        .collect()
+                })
+                .collect();
+            people.join("\n")
+        })
+        .collect();
+    groups.join("\n\n")
+}
+
+fn hash_set_everyone(input: &str) -> usize {
+    input
+        .blocks()
+        .map(|block| {
+            let mut lines = block.lines();
+            let mut all = HashSet::new();
+            if let Some(line) = lines.next() {
+                all.extend(line.chars())
+            };
+            lines.fold(all, |acc, line| {
+                let chars: HashSet<_> = line.chars().collect();
+                acc.intersection(&chars).cloned().collect()
+            })
+        })
+        .map(|set| set.len())
+        .sum()
+}
+
+fn answer_set_everyone(input: &str) -> usize {
+    input
+        .blocks()
+        .filter_map(|block| {
+            block
+                .lines()
+                .map(AnswerSet::from)
+                .reduce(|all, person| all.intersection(&person))
+        })
+        .map(|set| set.len())
+        .sum()
+}
+
+fn bench_answers(c: &mut Criterion) {
+    let input = large_input();
+    assert_eq!(hash_set_everyone(&input), answer_set_everyone(&input));
+
+    c.bench_function("anyone with hash sets", |b| {
+        b.iter(|| {
+            black_box(&input)
+                .blocks()
+                .map(|block| {
+                    block
+                        .chars()
+                        .filter(|c| c.is_alphabetic())
+                        .collect::<HashSet<_>>()
+                        .len()
+                })
+                .sum::<usize>()
+        })
+    });
+    c.bench_function("anyone with answer sets", |b| {
+        b.iter(|| {
+            black_box(&input)
+                .blocks()
+                .map(|block| {
+                    block
+                        .chars()
+                        .filter(|c| c.is_alphabetic())
+                        .collect::<AnswerSet>()
+                        .len()
+                })
+                .sum::<usize>()
+        })
+    });
+    c.bench_function("everyone with hash sets", |b| {
+        b.iter(|| hash_set_everyone(black_box(&input)))
+    });
+    c.bench_function("everyone with answer sets", |b| {
+        b.iter(|| answer_set_everyone(black_box(&input)))
+    });
+}
+
+criterion_group!(benches, bench_answers);
+criterion_main!(benches);
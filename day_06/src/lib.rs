@@ -0,0 +1,219 @@
+use common::Blocks;
+use std::collections::BTreeSet;
+use std::iter::FromIterator;
+use thiserror::Error;
+
+pub mod query;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum DeclarationError {
+    #[error("Group {group} is empty")]
+    EmptyGroup { group: usize },
+
+    #[error("Unexpected character {character:?} in group {group}, person {person}")]
+    InvalidCharacter {
+        group: usize,
+        person: usize,
+        character: char,
+    },
+}
+
+/// The questions someone answered with "yes". The letters `a..=z` are stored as bits of a
+/// mask, any other character falls back to a set that stays empty (and unallocated) for
+/// regular puzzle input.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AnswerSet {
+    letters: u32,
+    other: BTreeSet<char>,
+}
+
+fn letter_bit(answer: char) -> Option<u32> {
+    if answer.is_ascii_lowercase() {
+        Some(1 << (answer as u8 - b'a'))
+    } else {
+        None
+    }
+}
+
+impl AnswerSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an answer and returns whether it was new.
+    pub fn insert(&mut self, answer: char) -> bool {
+        match letter_bit(answer) {
+            Some(bit) => {
+                let new = self.letters & bit == 0;
+                self.letters |= bit;
+                new
+            }
+            None => self.other.insert(answer),
+        }
+    }
+
+    pub fn contains(&self, answer: char) -> bool {
+        match letter_bit(answer) {
+            Some(bit) => self.letters & bit != 0,
+            None => self.other.contains(&answer),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.letters.count_ones() as usize + self.other.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.letters == 0 && self.other.is_empty()
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            letters: self.letters | other.letters,
+            other: &self.other | &other.other,
+        }
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self {
+            letters: self.letters & other.letters,
+            other: &self.other & &other.other,
+        }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        Self {
+            letters: self.letters & !other.letters,
+            other: &self.other - &other.other,
+        }
+    }
+
+    /// Iterates over the answers, letters first in alphabetical order.
+    pub fn iter(&self) -> impl Iterator<Item = char> + '_ {
+        (b'a'..=b'z')
+            .map(char::from)
+            .filter(move |&answer| self.contains(answer))
+            .chain(self.other.iter().copied())
+    }
+}
+
+impl FromIterator<char> for AnswerSet {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for answer in iter {
+            set.insert(answer);
+        }
+        set
+    }
+}
+
+impl From<&str> for AnswerSet {
+    fn from(answers: &str) -> Self {
+        answers.chars().collect()
+    }
+}
+
+/// The answers of everyone in a group, one line per person.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Group {
+    pub people: Vec<AnswerSet>,
+}
+
+/// Parses all customs declarations. Every answer has to be one of the letters `a..=z`.
+/// Blank lines at the end of the input are ignored, any other empty group is an error.
+pub fn parse_groups(input: &str) -> Result<Vec<Group>, DeclarationError> {
+    input
+        .trim_end_matches('\n')
+        .blocks()
+        .enumerate()
+        .map(|(group, block)| {
+            if block.is_empty() {
+                return Err(DeclarationError::EmptyGroup { group });
+            }
+            let people = block
+                .lines()
+                .enumerate()
+                .map(
+                    |(person, line)| match line.chars().find(|c| !c.is_ascii_lowercase()) {
+                        Some(character) => Err(DeclarationError::InvalidCharacter {
+                            group,
+                            person,
+                            character,
+                        }),
+                        None => Ok(AnswerSet::from(line)),
+                    },
+                )
+                .collect::<Result<_, _>>()?;
+            Ok(Group { people })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_operations() {
+        let first = AnswerSet::from("abcz");
+        let second = AnswerSet::from("bcdy");
+        assert_eq!(first.len(), 4);
+        assert_eq!(first.union(&second), AnswerSet::from("abcdyz"));
+        assert_eq!(first.intersection(&second), AnswerSet::from("cb"));
+        assert_eq!(first.difference(&second), AnswerSet::from("az"));
+        assert!(first.intersection(&AnswerSet::from("xy")).is_empty());
+        assert_eq!(first.iter().collect::<String>(), "abcz");
+    }
+
+    #[test]
+    fn test_other_characters() {
+        let mut set = AnswerSet::from("aÄ1a");
+        assert_eq!(set.len(), 3);
+        assert!(!set.insert('1'));
+        assert!(set.insert('B'));
+        assert!(set.contains('Ä') && !set.contains('b'));
+        assert_eq!(set.iter().collect::<String>(), "a1BÄ");
+        let other = AnswerSet::from("1Äc");
+        assert_eq!(set.intersection(&other), AnswerSet::from("Ä1"));
+        assert_eq!(set.difference(&other), AnswerSet::from("aB"));
+        assert_eq!(set.union(&other).len(), 5);
+    }
+
+    #[test]
+    fn test_parse_groups() {
+        let groups = parse_groups("ab\nac\n\nb\n").unwrap();
+        assert_eq!(
+            groups,
+            vec![
+                Group {
+                    people: vec!["ab".into(), "ac".into()]
+                },
+                Group {
+                    people: vec!["b".into()]
+                }
+            ]
+        );
+        assert_eq!(
+            parse_groups("ab\n\nb\nc \n"),
+            Err(DeclarationError::InvalidCharacter {
+                group: 1,
+                person: 1,
+                character: ' '
+            })
+        );
+        assert_eq!(
+            parse_groups("ab\n\nbA"),
+            Err(DeclarationError::InvalidCharacter {
+                group: 1,
+                person: 0,
+                character: 'A'
+            })
+        );
+        assert_eq!(parse_groups("ab\n\n\n"), parse_groups("ab\n"));
+        assert_eq!(parse_groups("ab\n\n\n").unwrap().len(), 1);
+        assert_eq!(
+            parse_groups("\n\nab"),
+            Err(DeclarationError::EmptyGroup { group: 0 })
+        );
+    }
+}
@@ -1,51 +1,84 @@
-use common::{load_data_full, Blocks};
-use std::collections::HashSet;
-use std::iter::FromIterator;
+use anyhow::{anyhow, Result};
+use common::load_data_full;
+use day_06::query::{Histogram, Query};
+use day_06::{parse_groups, AnswerSet, Group};
+use std::env;
 
-fn main() {
-    let input: String = load_data_full("data/day_06.txt");
-    println!("Day 06 Part 1: {}", part_1(&input));
-    println!("Day 06 Part 2: {}", part_2(&input));
+const DEFAULT_INPUT: &str = "data/day_06.txt";
+
+/// Usage: `day_06 [--alphabet <letters>] [count <query> [<input>] | histogram [<input>]]`
+/// where a query is one of `anyone`, `everyone`, `at-least=<k>`, `exactly=<k>` or `nobody`.
+/// The alphabet lists the questions for `nobody` and defaults to `a..=z`.
+fn main() -> Result<()> {
+    let mut args = env::args().skip(1);
+    let mut alphabet = None;
+    let mut command = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--alphabet" => {
+                let letters = args
+                    .next()
+                    .ok_or_else(|| anyhow!("{} needs a value", arg))?;
+                alphabet = Some(AnswerSet::from(letters.as_str()));
+            }
+            _ => command.push(arg),
+        }
+    }
+
+    let command: Vec<_> = command.iter().map(String::as_str).collect();
+    match command.as_slice() {
+        [] => {
+            let input: String = load_data_full(DEFAULT_INPUT);
+            let groups = parse_groups(&input)?;
+            println!("Day 06 Part 1: {}", part_1(&groups));
+            println!("Day 06 Part 2: {}", part_2(&groups));
+        }
+        ["count", query, file @ ..] => {
+            let mut query: Query = query.parse().map_err(|e| anyhow!("{}", e))?;
+            if let (Query::Nobody(letters), Some(alphabet)) = (&mut query, alphabet) {
+                *letters = alphabet;
+            }
+            let input: String = load_data_full(file.first().unwrap_or(&DEFAULT_INPUT));
+            println!("{}", query.count(&parse_groups(&input)?));
+        }
+        ["histogram", file @ ..] => {
+            let input: String = load_data_full(file.first().unwrap_or(&DEFAULT_INPUT));
+            print_histogram(&Histogram::new(&parse_groups(&input)?));
+        }
+        _ => return Err(anyhow!("Unknown command {}", command.join(" "))),
+    }
+    Ok(())
+}
+
+fn print_histogram(histogram: &Histogram) {
+    let most = histogram.most_common();
+    println!("{:>8}  {:>6}  {:>6}", "question", "people", "groups");
+    for (answer, frequency) in &histogram.questions {
+        let bar = "#".repeat(frequency.people * 40 / most.1.max(1));
+        println!(
+            "{:>8}  {:>6}  {:>6}  {}",
+            answer, frequency.people, frequency.groups, bar
+        );
+    }
+    let least = histogram.least_common();
+    let join = |answers: Vec<char>| answers.into_iter().collect::<String>();
+    println!("most common: {} ({} people)", join(most.0), most.1);
+    println!("least common: {} ({} people)", join(least.0), least.1);
 }
 
-fn part_1(input: &str) -> usize {
-    input
-        .blocks()
-        .map(|block| {
-            block.chars().fold(HashSet::new(), |mut acc, c| {
-                if c.is_alphabetic() {
-                    acc.insert(c);
-                }
-                acc
-            })
-        })
-        .map(|set| set.len())
-        .sum()
+fn part_1(groups: &[Group]) -> usize {
+    Query::Anyone.count(groups)
 }
 
-fn part_2(input: &str) -> usize {
-    input
-        .blocks()
-        .map(|block| {
-            let mut lines = block.lines();
-            let mut all = HashSet::new();
-            if let Some(line) = lines.next() {
-                all.extend(line.chars())
-            };
-            lines.fold(all, |acc, line| {
-                let chars = HashSet::from_iter(line.chars());
-                acc.intersection(&chars).cloned().collect()
-            })
-        })
-        .map(|set| set.len())
-        .sum()
+fn part_2(groups: &[Group]) -> usize {
+    Query::Everyone.count(groups)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn get_input() -> String {
+    fn get_input() -> Vec<Group> {
         let data = "\
 abc
 
@@ -62,7 +95,7 @@ a
 a
 
 b";
-        data.to_owned()
+        parse_groups(data).unwrap()
     }
 
     #[test]
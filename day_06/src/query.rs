@@ -0,0 +1,198 @@
+use crate::{AnswerSet, Group};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// How many people of a group answered each question. Questions nobody answered are left out.
+pub fn answer_counts(people: &[AnswerSet]) -> BTreeMap<char, usize> {
+    let mut counts = BTreeMap::new();
+    for answer in people.iter().flat_map(AnswerSet::iter) {
+        *counts.entry(answer).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Which answers to select from each group.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Query {
+    /// Answered by anyone in the group.
+    Anyone,
+    /// Answered by everyone in the group.
+    Everyone,
+    /// Answered by at least this many people.
+    AtLeast(usize),
+    /// Answered by exactly this many people.
+    Exactly(usize),
+    /// Answered by nobody in the group, out of the given alphabet.
+    Nobody(AnswerSet),
+}
+
+impl Query {
+    pub fn evaluate(&self, people: &[AnswerSet]) -> AnswerSet {
+        match self {
+            Query::Anyone => people
+                .iter()
+                .fold(AnswerSet::new(), |all, person| all.union(person)),
+            Query::Everyone => people
+                .iter()
+                .cloned()
+                .reduce(|all, person| all.intersection(&person))
+                .unwrap_or_default(),
+            Query::AtLeast(k) => Self::select(people, |count| count >= *k),
+            Query::Exactly(k) => Self::select(people, |count| count == *k),
+            Query::Nobody(alphabet) => alphabet.difference(&Query::Anyone.evaluate(people)),
+        }
+    }
+
+    fn select<F: Fn(usize) -> bool>(people: &[AnswerSet], predicate: F) -> AnswerSet {
+        answer_counts(people)
+            .into_iter()
+            .filter(|&(_, count)| predicate(count))
+            .map(|(answer, _)| answer)
+            .collect()
+    }
+
+    /// Sums the number of selected answers over all groups.
+    pub fn count(&self, groups: &[Group]) -> usize {
+        groups
+            .iter()
+            .map(|group| self.evaluate(&group.people).len())
+            .sum()
+    }
+}
+
+/// Parses `anyone`, `everyone`, `at-least=<k>` and `exactly=<k>` with a positive `k`, and
+/// `nobody` which uses the letters `a..=z` as alphabet.
+impl FromStr for Query {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let people = |k: &str| match k.parse() {
+            Ok(k) if k > 0 => Ok(k),
+            _ => Err(format!("Invalid number of people {}", k)),
+        };
+        match value.split_once('=') {
+            None if value == "anyone" => Ok(Query::Anyone),
+            None if value == "everyone" => Ok(Query::Everyone),
+            None if value == "nobody" => {
+                Ok(Query::Nobody(AnswerSet::from("abcdefghijklmnopqrstuvwxyz")))
+            }
+            Some(("at-least", k)) => Ok(Query::AtLeast(people(k)?)),
+            Some(("exactly", k)) => Ok(Query::Exactly(people(k)?)),
+            _ => Err(format!("Unknown query {}", value)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Frequency {
+    pub people: usize,
+    pub groups: usize,
+}
+
+/// How often each question was answered across all groups.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Histogram {
+    pub questions: BTreeMap<char, Frequency>,
+}
+
+impl Histogram {
+    pub fn new(groups: &[Group]) -> Self {
+        let mut histogram = Self::default();
+        for group in groups {
+            for (answer, count) in answer_counts(&group.people) {
+                let frequency = histogram.questions.entry(answer).or_default();
+                frequency.people += count;
+                frequency.groups += 1;
+            }
+        }
+        histogram
+    }
+
+    /// The answers given by the most people, together with their number.
+    pub fn most_common(&self) -> (Vec<char>, usize) {
+        self.extreme(|count, best| count > best)
+    }
+
+    /// The answers given by the fewest people, ignoring questions nobody answered.
+    pub fn least_common(&self) -> (Vec<char>, usize) {
+        self.extreme(|count, best| count < best)
+    }
+
+    fn extreme<F: Fn(usize, usize) -> bool>(&self, better: F) -> (Vec<char>, usize) {
+        let mut answers = Vec::new();
+        let mut best = 0;
+        for (&answer, frequency) in &self.questions {
+            if answers.is_empty() || better(frequency.people, best) {
+                answers.clear();
+                best = frequency.people;
+            }
+            if frequency.people == best {
+                answers.push(answer);
+            }
+        }
+        (answers, best)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_groups;
+
+    fn get_groups() -> Vec<Group> {
+        parse_groups("abc\n\nab\nac\na\n\nb\nbx").unwrap()
+    }
+
+    #[test]
+    fn test_queries() {
+        let groups = get_groups();
+        let people = &groups[1].people;
+        assert_eq!(Query::Anyone.evaluate(people), AnswerSet::from("abc"));
+        assert_eq!(Query::Everyone.evaluate(people), AnswerSet::from("a"));
+        assert_eq!(Query::AtLeast(2).evaluate(people), AnswerSet::from("a"));
+        assert_eq!(Query::Exactly(1).evaluate(people), AnswerSet::from("bc"));
+        let alphabet = AnswerSet::from("abcd");
+        assert_eq!(
+            Query::Nobody(alphabet).evaluate(people),
+            AnswerSet::from("d")
+        );
+        assert!(Query::Everyone.evaluate(&[]).is_empty());
+
+        assert_eq!(Query::Exactly(2).count(&groups), 1);
+        assert_eq!(Query::Anyone.count(&groups), 8);
+        assert_eq!("nobody".parse::<Query>().unwrap().count(&groups), 70);
+    }
+
+    #[test]
+    fn test_parse_query() {
+        assert_eq!("anyone".parse(), Ok(Query::Anyone));
+        assert_eq!("at-least=3".parse(), Ok(Query::AtLeast(3)));
+        assert_eq!("exactly=1".parse(), Ok(Query::Exactly(1)));
+        assert!("exactly=0".parse::<Query>().is_err());
+        assert!("at-least".parse::<Query>().is_err());
+        assert!("someone".parse::<Query>().is_err());
+    }
+
+    #[test]
+    fn test_histogram() {
+        let groups = get_groups();
+        let histogram = Histogram::new(&groups);
+        assert_eq!(
+            histogram.questions[&'a'],
+            Frequency {
+                people: 4,
+                groups: 2
+            }
+        );
+        assert_eq!(
+            histogram.questions[&'b'],
+            Frequency {
+                people: 4,
+                groups: 3
+            }
+        );
+        assert_eq!(histogram.most_common(), (vec!['a', 'b'], 4));
+        assert_eq!(histogram.least_common(), (vec!['x'], 1));
+        assert_eq!(Histogram::default().most_common(), (vec![], 0));
+    }
+}
@@ -0,0 +1,193 @@
+use crate::{BagGraph, BagId};
+use std::fmt::Write;
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Format {
+    Dot,
+    Mermaid,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "dot" => Ok(Format::Dot),
+            "mermaid" => Ok(Format::Mermaid),
+            _ => Err(format!("Unknown format {}", value)),
+        }
+    }
+}
+
+/// Which bags to export.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Scope {
+    All,
+    /// The bag and all bags that eventually contain it.
+    Ancestors(BagId),
+    /// The bag and all bags eventually inside it.
+    Descendants(BagId),
+}
+
+/// Writes the rules as a graph with an edge from each bag to the bags inside it, labelled
+/// with their quantity.
+#[derive(Copy, Clone, Debug)]
+pub struct GraphWriter {
+    pub format: Format,
+    pub scope: Scope,
+    /// The bag to highlight, usually the one the scope is based on.
+    pub highlight: Option<BagId>,
+}
+
+impl GraphWriter {
+    pub fn new(format: Format, scope: Scope) -> Self {
+        let highlight = match scope {
+            Scope::All => None,
+            Scope::Ancestors(bag) | Scope::Descendants(bag) => Some(bag),
+        };
+        Self {
+            format,
+            scope,
+            highlight,
+        }
+    }
+
+    fn included(&self, graph: &BagGraph) -> Vec<bool> {
+        let (bag, others) = match self.scope {
+            Scope::All => return vec![true; graph.len()],
+            Scope::Ancestors(bag) => (bag, graph.ancestors(bag)),
+            Scope::Descendants(bag) => (bag, graph.descendants(bag)),
+        };
+        let mut included = vec![false; graph.len()];
+        for bag in others.into_iter().chain(std::iter::once(bag)) {
+            included[bag] = true;
+        }
+        included
+    }
+
+    pub fn write(&self, graph: &BagGraph) -> String {
+        let included = self.included(graph);
+        let bags: Vec<_> = (0..graph.len()).filter(|&bag| included[bag]).collect();
+        let edges: Vec<_> = bags
+            .iter()
+            .flat_map(|&bag| {
+                graph
+                    .contents(bag)
+                    .iter()
+                    .filter(|&&(_, content)| included[content])
+                    .map(move |&(count, content)| (bag, count, content))
+            })
+            .collect();
+        match self.format {
+            Format::Dot => self.write_dot(graph, &bags, &edges),
+            Format::Mermaid => self.write_mermaid(graph, &bags, &edges),
+        }
+    }
+
+    fn write_dot(
+        &self,
+        graph: &BagGraph,
+        bags: &[BagId],
+        edges: &[(BagId, usize, BagId)],
+    ) -> String {
+        let name = |bag| format!("\"{}\"", graph.name(bag).replace('"', "\\\""));
+        let mut output = String::from("digraph bags {\n");
+        for &bag in bags {
+            if self.highlight == Some(bag) {
+                writeln!(output, "    {} [style=filled, fillcolor=gold];", name(bag)).unwrap();
+            } else {
+                writeln!(output, "    {};", name(bag)).unwrap();
+            }
+        }
+        for &(bag, count, content) in edges {
+            writeln!(
+                output,
+                "    {} -> {} [label=\"{}\"];",
+                name(bag),
+                name(content),
+                count
+            )
+            .unwrap();
+        }
+        output.push_str("}\n");
+        output
+    }
+
+    fn write_mermaid(
+        &self,
+        graph: &BagGraph,
+        bags: &[BagId],
+        edges: &[(BagId, usize, BagId)],
+    ) -> String {
+        let mut output = String::from("graph TD\n");
+        for &bag in bags {
+            let name = graph.name(bag).replace('"', "#quot;");
+            writeln!(output, "    bag{}[\"{}\"]", bag, name).unwrap();
+        }
+        for &(bag, count, content) in edges {
+            writeln!(output, "    bag{} -->|{}| bag{}", bag, count, content).unwrap();
+        }
+        // Styling a bag outside the scope would make Mermaid draw it as an extra node.
+        if let Some(bag) = self.highlight.filter(|bag| bags.contains(bag)) {
+            writeln!(output, "    style bag{} fill:#ffd700", bag).unwrap();
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "\
+light red bags contain 1 bright white bag.
+bright white bags contain 1 shiny gold bag.
+shiny gold bags contain 2 dark olive bags.
+dark olive bags contain no other bags.
+faded blue bags contain 3 dark olive bags.";
+
+    #[test]
+    fn test_dot() {
+        let graph = BagGraph::parse(INPUT).unwrap();
+        let gold = graph.id("shiny gold").unwrap();
+        assert_eq!(
+            GraphWriter::new(Format::Dot, Scope::Descendants(gold)).write(&graph),
+            "\
+digraph bags {
+    \"shiny gold\" [style=filled, fillcolor=gold];
+    \"dark olive\";
+    \"shiny gold\" -> \"dark olive\" [label=\"2\"];
+}
+"
+        );
+        let output = GraphWriter::new(Format::Dot, Scope::All).write(&graph);
+        assert_eq!(output.lines().count(), 2 + 5 + 4);
+        assert!(!output.contains("fillcolor"));
+    }
+
+    #[test]
+    fn test_mermaid() {
+        let graph = BagGraph::parse(INPUT).unwrap();
+        let gold = graph.id("shiny gold").unwrap();
+        assert_eq!(
+            GraphWriter::new(Format::Mermaid, Scope::Ancestors(gold)).write(&graph),
+            "\
+graph TD
+    bag0[\"light red\"]
+    bag1[\"bright white\"]
+    bag2[\"shiny gold\"]
+    bag0 -->|1| bag1
+    bag1 -->|1| bag2
+    style bag2 fill:#ffd700
+"
+        );
+        let mut writer = GraphWriter::new(Format::Mermaid, Scope::Descendants(gold));
+        writer.highlight = graph.id("light red").ok();
+        let output = writer.write(&graph);
+        assert!(!output.contains("style"));
+        assert!(!output.contains("bag0"));
+        assert_eq!("mermaid".parse(), Ok(Format::Mermaid));
+        assert!("svg".parse::<Format>().is_err());
+    }
+}
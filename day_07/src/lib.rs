@@ -0,0 +1,614 @@
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use std::collections::{HashMap, VecDeque};
+use thiserror::Error;
+
+pub mod export;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum GraphError {
+    #[error("Invalid rule {0:?}")]
+    InvalidRule(String),
+
+    #[error("Duplicate rule for {0} bags")]
+    DuplicateRule(String),
+
+    #[error("Unknown bag {0}")]
+    UnknownBag(String),
+
+    #[error("No rule for {0} bags")]
+    MissingRule(String),
+
+    /// The colours along the cycle, starting and ending with the same one.
+    #[error("Bags contain themselves: {}", .path.join(" -> "))]
+    Cycle { path: Vec<String> },
+
+    #[error("Too many bags inside {0} bags")]
+    Overflow(String),
+}
+
+pub type Rule<'a> = (&'a str, Vec<(usize, &'a str)>);
+
+pub fn parse_color(input: &str) -> Result<&str, GraphError> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"(.+) bags?").unwrap();
+    }
+    let captures: Captures = RE
+        .captures(input)
+        .ok_or_else(|| GraphError::InvalidRule(input.to_owned()))?;
+    Ok(captures.get(1).unwrap().as_str())
+}
+
+pub fn parse_rule(input: &str) -> Result<Rule<'_>, GraphError> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"(.+) contain (.+)\.").unwrap();
+        static ref CONTENT: Regex = Regex::new(r"(\d+) (.+)").unwrap();
+    }
+    let invalid = || GraphError::InvalidRule(input.to_owned());
+    let captures: Captures = RE.captures(input).ok_or_else(invalid)?;
+    let color = parse_color(captures.get(1).unwrap().as_str())?;
+    let contents = captures.get(2).unwrap().as_str();
+    if contents == "no other bags" {
+        return Ok((color, vec![]));
+    };
+    let contents: Result<Vec<_>, GraphError> = contents
+        .split(", ")
+        .map(|desc| {
+            let desc = CONTENT.captures(desc).ok_or_else(invalid)?;
+            Ok((
+                desc.get(1)
+                    .unwrap()
+                    .as_str()
+                    .parse()
+                    .map_err(|_| invalid())?,
+                parse_color(desc.get(2).unwrap().as_str())?,
+            ))
+        })
+        .collect();
+    Ok((color, contents?))
+}
+
+pub fn parse_rules(input: &str) -> Result<Vec<Rule<'_>>, GraphError> {
+    input.lines().map(parse_rule).collect()
+}
+
+/// Index of a bag colour in a [`BagGraph`].
+pub type BagId = usize;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Visit {
+    New,
+    Active,
+    Done,
+}
+
+/// The rules as a directed graph from each bag to the bags it directly contains, weighted
+/// with their quantity. Colours are interned so traversals only deal with indices.
+#[derive(Clone, Debug, Default)]
+pub struct BagGraph<'a> {
+    names: Vec<&'a str>,
+    ids: HashMap<&'a str, BagId>,
+    /// Whether a rule exists for the bag, as opposed to only being mentioned as content.
+    defined: Vec<bool>,
+    contents: Vec<Vec<(usize, BagId)>>,
+    containers: Vec<Vec<(usize, BagId)>>,
+}
+
+impl<'a> BagGraph<'a> {
+    pub fn new(rules: Vec<Rule<'a>>) -> Result<Self, GraphError> {
+        let mut graph = Self::default();
+        for (color, contents) in rules {
+            let bag = graph.intern(color);
+            if graph.defined[bag] {
+                return Err(GraphError::DuplicateRule(color.to_owned()));
+            }
+            graph.defined[bag] = true;
+            for (count, content) in contents {
+                let content = graph.intern(content);
+                graph.contents[bag].push((count, content));
+                graph.containers[content].push((count, bag));
+            }
+        }
+        Ok(graph)
+    }
+
+    pub fn parse(input: &'a str) -> Result<Self, GraphError> {
+        Self::new(parse_rules(input)?)
+    }
+
+    fn intern(&mut self, color: &'a str) -> BagId {
+        if let Some(&id) = self.ids.get(color) {
+            return id;
+        }
+        let id = self.names.len();
+        self.names.push(color);
+        self.ids.insert(color, id);
+        self.defined.push(false);
+        self.contents.push(Vec::new());
+        self.containers.push(Vec::new());
+        id
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn id(&self, color: &str) -> Result<BagId, GraphError> {
+        self.ids
+            .get(color)
+            .copied()
+            .ok_or_else(|| GraphError::UnknownBag(color.to_owned()))
+    }
+
+    pub fn name(&self, bag: BagId) -> &'a str {
+        self.names[bag]
+    }
+
+    pub fn is_defined(&self, bag: BagId) -> bool {
+        self.defined[bag]
+    }
+
+    /// The bags directly inside `bag` with their quantity.
+    pub fn contents(&self, bag: BagId) -> &[(usize, BagId)] {
+        &self.contents[bag]
+    }
+
+    /// The bags directly containing `bag` with the quantity of `bag` inside them.
+    pub fn containers(&self, bag: BagId) -> &[(usize, BagId)] {
+        &self.containers[bag]
+    }
+
+    fn reachable(&self, start: BagId, edges: &[Vec<(usize, BagId)>]) -> Vec<BagId> {
+        let mut seen = vec![false; self.len()];
+        let mut queue = VecDeque::new();
+        let mut found = Vec::new();
+        seen[start] = true;
+        queue.push_back(start);
+        while let Some(bag) = queue.pop_front() {
+            for &(_, next) in &edges[bag] {
+                if !seen[next] {
+                    seen[next] = true;
+                    found.push(next);
+                    queue.push_back(next);
+                }
+            }
+        }
+        found
+    }
+
+    /// All bags that eventually contain `bag`, nearest first.
+    pub fn ancestors(&self, bag: BagId) -> Vec<BagId> {
+        self.reachable(bag, &self.containers)
+    }
+
+    /// All bags eventually inside `bag`, nearest first.
+    pub fn descendants(&self, bag: BagId) -> Vec<BagId> {
+        self.reachable(bag, &self.contents)
+    }
+
+    /// Walks the contents of `start` depth first with an explicit stack and calls `finish` for
+    /// every bag once all bags inside it are finished. Bags already marked as done are skipped,
+    /// so the state can be shared between walks.
+    fn depth_first<F>(
+        &self,
+        start: BagId,
+        state: &mut [Visit],
+        mut finish: F,
+    ) -> Result<(), GraphError>
+    where
+        F: FnMut(BagId) -> Result<(), GraphError>,
+    {
+        if state[start] == Visit::Done {
+            return Ok(());
+        }
+        state[start] = Visit::Active;
+        let mut stack = vec![(start, 0)];
+        while let Some(&(bag, edge)) = stack.last() {
+            match self.contents[bag].get(edge) {
+                Some(&(_, next)) => {
+                    stack.last_mut().unwrap().1 += 1;
+                    match state[next] {
+                        Visit::New => {
+                            state[next] = Visit::Active;
+                            stack.push((next, 0));
+                        }
+                        Visit::Active => {
+                            let first = stack.iter().position(|&(bag, _)| bag == next).unwrap();
+                            let path = stack[first..]
+                                .iter()
+                                .map(|&(bag, _)| bag)
+                                .chain(std::iter::once(next))
+                                .map(|bag| self.name(bag).to_owned())
+                                .collect();
+                            return Err(GraphError::Cycle { path });
+                        }
+                        Visit::Done => {}
+                    }
+                }
+                None => {
+                    state[bag] = Visit::Done;
+                    stack.pop();
+                    finish(bag)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that every bag has a rule and that no bag eventually contains itself.
+    pub fn validate(&self) -> Result<(), GraphError> {
+        let mut state = vec![Visit::New; self.len()];
+        for bag in 0..self.len() {
+            self.depth_first(bag, &mut state, |bag| {
+                if self.defined[bag] {
+                    Ok(())
+                } else {
+                    Err(GraphError::MissingRule(self.name(bag).to_owned()))
+                }
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Computes the number of bags inside `bag` from the totals of the bags it contains.
+    fn total(&self, bag: BagId, totals: &[usize]) -> Result<usize, GraphError> {
+        if !self.defined[bag] {
+            return Err(GraphError::MissingRule(self.name(bag).to_owned()));
+        }
+        self.contents[bag]
+            .iter()
+            .try_fold(0usize, |sum, &(count, content)| {
+                totals[content]
+                    .checked_add(1)
+                    .and_then(|bags| bags.checked_mul(count))
+                    .and_then(|bags| bags.checked_add(sum))
+            })
+            .ok_or_else(|| GraphError::Overflow(self.name(bag).to_owned()))
+    }
+
+    /// The total number of bags inside `bag`. The total of each bag inside is computed once,
+    /// no matter how many bags contain it.
+    pub fn count_contents(&self, bag: BagId) -> Result<usize, GraphError> {
+        let mut state = vec![Visit::New; self.len()];
+        let mut totals = vec![0; self.len()];
+        self.depth_first(bag, &mut state, |bag| {
+            totals[bag] = self.total(bag, &totals)?;
+            Ok(())
+        })?;
+        Ok(totals[bag])
+    }
+
+    /// Orders all bags so that every bag comes after the bags inside it.
+    pub fn topological_order(&self) -> Result<Vec<BagId>, GraphError> {
+        let mut state = vec![Visit::New; self.len()];
+        let mut order = Vec::with_capacity(self.len());
+        for bag in 0..self.len() {
+            self.depth_first(bag, &mut state, |bag| {
+                order.push(bag);
+                Ok(())
+            })?;
+        }
+        Ok(order)
+    }
+
+    /// The total number of bags inside every bag, indexed by [`BagId`], computed in a single
+    /// pass over the topological order.
+    pub fn count_all(&self) -> Result<Vec<usize>, GraphError> {
+        let mut totals = vec![0; self.len()];
+        for bag in self.topological_order()? {
+            totals[bag] = self.total(bag, &totals)?;
+        }
+        Ok(totals)
+    }
+
+    /// How many of each bag end up inside `bag`, nearest bags first.
+    pub fn content_quantities(&self, bag: BagId) -> Result<Vec<(BagId, usize)>, GraphError> {
+        let mut state = vec![Visit::New; self.len()];
+        let mut order = Vec::new();
+        self.depth_first(bag, &mut state, |bag| {
+            if !self.defined[bag] {
+                return Err(GraphError::MissingRule(self.name(bag).to_owned()));
+            }
+            order.push(bag);
+            Ok(())
+        })?;
+        let mut quantities = vec![0usize; self.len()];
+        quantities[bag] = 1;
+        for &outer in order.iter().rev() {
+            for &(count, content) in &self.contents[outer] {
+                quantities[content] = quantities[outer]
+                    .checked_mul(count)
+                    .and_then(|bags| bags.checked_add(quantities[content]))
+                    .ok_or_else(|| GraphError::Overflow(self.name(bag).to_owned()))?;
+            }
+        }
+        Ok(self
+            .descendants(bag)
+            .into_iter()
+            .map(|content| (content, quantities[content]))
+            .collect())
+    }
+
+    /// The shortest chain of bags from `from` down to `to`, as the quantity and colour of
+    /// each bag inside the previous one.
+    pub fn path(&self, from: BagId, to: BagId) -> Option<Vec<(usize, BagId)>> {
+        let mut previous = vec![None; self.len()];
+        let mut queue = VecDeque::new();
+        queue.push_back(from);
+        while let Some(bag) = queue.pop_front() {
+            if bag == to {
+                let mut path = Vec::new();
+                let mut bag = to;
+                while let Some((count, outer)) = previous[bag] {
+                    path.push((count, bag));
+                    bag = outer;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for &(count, content) in &self.contents[bag] {
+                if content != from && previous[content].is_none() {
+                    previous[content] = Some((count, bag));
+                    queue.push_back(content);
+                }
+            }
+        }
+        None
+    }
+
+    /// Formats a path from [`BagGraph::path`] like `1 light red > 2 muted yellow > 4 shiny gold`,
+    /// where each quantity is the number of bags of that colour along the chain.
+    pub fn format_path(&self, from: BagId, path: &[(usize, BagId)]) -> Result<String, GraphError> {
+        let mut quantity: usize = 1;
+        let mut output = format!("1 {}", self.name(from));
+        for &(count, bag) in path {
+            quantity = quantity
+                .checked_mul(count)
+                .ok_or_else(|| GraphError::Overflow(self.name(from).to_owned()))?;
+            output.push_str(&format!(" > {} {}", quantity, self.name(bag)));
+        }
+        Ok(output)
+    }
+
+    /// The number of levels of bags inside `bag`, 0 for a bag that contains no other bags.
+    pub fn depth(&self, bag: BagId) -> Result<usize, GraphError> {
+        let mut state = vec![Visit::New; self.len()];
+        let mut depths = vec![0; self.len()];
+        self.depth_first(bag, &mut state, |bag| {
+            if !self.defined[bag] {
+                return Err(GraphError::MissingRule(self.name(bag).to_owned()));
+            }
+            depths[bag] = self.contents[bag]
+                .iter()
+                .map(|&(_, content)| depths[content] + 1)
+                .max()
+                .unwrap_or(0);
+            Ok(())
+        })?;
+        Ok(depths[bag])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "\
+light red bags contain 1 bright white bag, 2 muted yellow bags.
+bright white bags contain 1 shiny gold bag.
+muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
+shiny gold bags contain 1 dark olive bag.
+dark olive bags contain 3 faded blue bags.
+faded blue bags contain no other bags.";
+
+    fn names<'a>(graph: &BagGraph<'a>, bags: Vec<BagId>) -> Vec<&'a str> {
+        bags.into_iter().map(|bag| graph.name(bag)).collect()
+    }
+
+    #[test]
+    fn test_adjacency() {
+        let graph = BagGraph::parse(INPUT).unwrap();
+        assert_eq!(graph.len(), 6);
+        let gold = graph.id("shiny gold").unwrap();
+        let yellow = graph.id("muted yellow").unwrap();
+        assert_eq!(
+            graph.contents(yellow),
+            &[(2, gold), (9, graph.id("faded blue").unwrap())]
+        );
+        assert_eq!(
+            graph.containers(gold),
+            &[(1, graph.id("bright white").unwrap()), (2, yellow)]
+        );
+        assert_eq!(
+            graph.id("plaid purple"),
+            Err(GraphError::UnknownBag("plaid purple".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_traversals() {
+        let graph = BagGraph::parse(INPUT).unwrap();
+        let gold = graph.id("shiny gold").unwrap();
+        assert_eq!(
+            names(&graph, graph.ancestors(gold)),
+            vec!["bright white", "muted yellow", "light red"]
+        );
+        assert_eq!(
+            names(&graph, graph.descendants(gold)),
+            vec!["dark olive", "faded blue"]
+        );
+        let blue = graph.id("faded blue").unwrap();
+        assert_eq!(graph.ancestors(blue).len(), 5);
+        assert!(graph.descendants(blue).is_empty());
+    }
+
+    #[test]
+    fn test_cycles() {
+        let graph = BagGraph::parse(
+            "\
+light red bags contain 1 bright white bag.
+bright white bags contain 2 muted yellow bags, 1 faded blue bag.
+faded blue bags contain no other bags.
+muted yellow bags contain 3 light red bags.",
+        )
+        .unwrap();
+        let cycle = GraphError::Cycle {
+            path: vec![
+                "light red".to_owned(),
+                "bright white".to_owned(),
+                "muted yellow".to_owned(),
+                "light red".to_owned(),
+            ],
+        };
+        assert_eq!(graph.validate(), Err(cycle.clone()));
+        assert_eq!(
+            cycle.to_string(),
+            "Bags contain themselves: light red -> bright white -> muted yellow -> light red"
+        );
+        let white = graph.id("bright white").unwrap();
+        assert!(matches!(
+            graph.count_contents(white),
+            Err(GraphError::Cycle { .. })
+        ));
+        assert_eq!(graph.count_contents(graph.id("faded blue").unwrap()), Ok(0));
+
+        let graph = BagGraph::parse("shiny gold bags contain 2 shiny gold bags.").unwrap();
+        assert_eq!(
+            graph.validate(),
+            Err(GraphError::Cycle {
+                path: vec!["shiny gold".to_owned(), "shiny gold".to_owned()]
+            })
+        );
+    }
+
+    #[test]
+    fn test_deep_chain() {
+        let mut input: String = (0..20_000)
+            .map(|i| format!("bag {} bags contain 1 bag {} bag.\n", i, i + 1))
+            .collect();
+        input.push_str("bag 20000 bags contain no other bags.");
+        let graph = BagGraph::parse(&input).unwrap();
+        assert_eq!(graph.validate(), Ok(()));
+        assert_eq!(graph.count_contents(graph.id("bag 0").unwrap()), Ok(20_000));
+    }
+
+    #[test]
+    fn test_count_all() {
+        let graph = BagGraph::parse(INPUT).unwrap();
+        let order = graph.topological_order().unwrap();
+        assert_eq!(order.len(), graph.len());
+        for (position, &bag) in order.iter().enumerate() {
+            for &(_, content) in graph.contents(bag) {
+                assert!(order[..position].contains(&content));
+            }
+        }
+        let totals = graph.count_all().unwrap();
+        let total = |color| totals[graph.id(color).unwrap()];
+        assert_eq!(total("faded blue"), 0);
+        assert_eq!(total("dark olive"), 3);
+        assert_eq!(total("shiny gold"), 4);
+        assert_eq!(total("muted yellow"), 2 * 5 + 9);
+        assert_eq!(total("light red"), (1 + 5) + 2 * (1 + 19));
+        for (bag, &total) in totals.iter().enumerate() {
+            assert_eq!(graph.count_contents(bag), Ok(total));
+        }
+    }
+
+    #[test]
+    fn test_overflow() {
+        let mut input: String = (0..20)
+            .map(|i| format!("bag {} bags contain 10000 bag {} bags.\n", i, i + 1))
+            .collect();
+        input.push_str("bag 20 bags contain no other bags.");
+        let graph = BagGraph::parse(&input).unwrap();
+        let overflow = GraphError::Overflow("bag 15".to_owned());
+        assert_eq!(graph.count_contents(0), Err(overflow.clone()));
+        assert_eq!(graph.count_all(), Err(overflow));
+        let path = graph.path(0, graph.id("bag 20").unwrap()).unwrap();
+        assert_eq!(
+            graph.format_path(0, &path),
+            Err(GraphError::Overflow("bag 0".to_owned()))
+        );
+        assert_eq!(
+            graph.count_contents(graph.id("bag 16").unwrap()),
+            Ok((1..=4).map(|level| 10_000usize.pow(level)).sum())
+        );
+    }
+
+    #[test]
+    fn test_queries() {
+        let graph = BagGraph::parse(INPUT).unwrap();
+        let id = |color| graph.id(color).unwrap();
+        assert_eq!(
+            graph.content_quantities(id("muted yellow")).unwrap(),
+            vec![
+                (id("shiny gold"), 2),
+                (id("faded blue"), 9 + 2 * 3),
+                (id("dark olive"), 2)
+            ]
+        );
+        let total: usize = graph
+            .content_quantities(id("light red"))
+            .unwrap()
+            .iter()
+            .map(|&(_, quantity)| quantity)
+            .sum();
+        assert_eq!(total, graph.count_contents(id("light red")).unwrap());
+
+        assert_eq!(
+            graph.path(id("light red"), id("dark olive")),
+            Some(vec![
+                (1, id("bright white")),
+                (1, id("shiny gold")),
+                (1, id("dark olive"))
+            ])
+        );
+        assert_eq!(
+            graph.path(id("muted yellow"), id("faded blue")),
+            Some(vec![(9, id("faded blue"))])
+        );
+        assert_eq!(graph.path(id("shiny gold"), id("shiny gold")), Some(vec![]));
+        let path = graph.path(id("light red"), id("faded blue")).unwrap();
+        assert_eq!(
+            graph.format_path(id("light red"), &path),
+            Ok("1 light red > 2 muted yellow > 18 faded blue".to_owned())
+        );
+        assert_eq!(graph.path(id("shiny gold"), id("light red")), None);
+
+        assert_eq!(graph.depth(id("light red")), Ok(4));
+        assert_eq!(graph.depth(id("faded blue")), Ok(0));
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        assert_eq!(
+            BagGraph::parse(
+                "faded blue bags contain no other bags.\n\
+faded blue bags contain 1 dark olive bag."
+            )
+            .unwrap_err(),
+            GraphError::DuplicateRule("faded blue".to_owned())
+        );
+        assert_eq!(
+            BagGraph::parse("shiny gold bags contain many bags.").unwrap_err(),
+            GraphError::InvalidRule("shiny gold bags contain many bags.".to_owned())
+        );
+        let graph = BagGraph::parse("shiny gold bags contain 1 dark olive bag.").unwrap();
+        assert!(!graph.is_defined(graph.id("dark olive").unwrap()));
+        let missing = GraphError::MissingRule("dark olive".to_owned());
+        assert_eq!(graph.validate(), Err(missing.clone()));
+        assert_eq!(
+            graph.count_contents(graph.id("shiny gold").unwrap()),
+            Err(missing.clone())
+        );
+        assert_eq!(
+            graph.content_quantities(graph.id("shiny gold").unwrap()),
+            Err(missing.clone())
+        );
+        assert_eq!(graph.depth(graph.id("shiny gold").unwrap()), Err(missing));
+    }
+}
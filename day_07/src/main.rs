@@ -1,97 +1,127 @@
 use anyhow::{anyhow, Result};
 use common::load_data_full;
-use lazy_static::lazy_static;
-use regex::{Captures, Regex};
-use std::collections::{HashMap, HashSet};
-
-fn main() {
-    let input: String = load_data_full("data/day_07.txt");
-    println!("Day 07 Part 1: {}", part_1(&input));
-    println!("Day 07  Part 2: {}", part_2(&input));
-}
-
-fn parse_color(input: &str) -> Result<&str> {
-    lazy_static! {
-        static ref RE: Regex = Regex::new(r"(.+) bags?").unwrap();
-    }
-    let captures: Captures = RE.captures(input).ok_or(anyhow!("Not a valid bag"))?;
-    Ok(captures.get(1).unwrap().as_str())
-}
-
-fn parse_rule(input: &str) -> Result<(&str, Vec<(usize, &str)>)> {
-    lazy_static! {
-        static ref RE: Regex = Regex::new(r"(.+) contain (.+)\.").unwrap();
-        static ref CONTENT: Regex = Regex::new(r"(\d+) (.+)").unwrap();
+use day_07::export::{Format, GraphWriter, Scope};
+use day_07::BagGraph;
+use std::env;
+
+const DEFAULT_INPUT: &str = "data/day_07.txt";
+const DEFAULT_TARGET: &str = "shiny gold";
+
+/// Usage: `day_07 [containers-of <color> [<input>] | contents-of <color> [<input>]
+/// | path <from> <to> [<input>] | depth <color> [<input>]
+/// | export [--format <format>] [--ancestors <color> | --descendants <color>]
+/// [--highlight <color>] [<input>]]` where a format is one of `dot` or `mermaid`.
+/// Colours with spaces need to be quoted, e.g. `day_07 depth "shiny gold"`.
+fn main() -> Result<()> {
+    let args: Vec<_> = env::args().skip(1).collect();
+    let args: Vec<_> = args.iter().map(String::as_str).collect();
+    let load = |file: &[&str]| -> String { load_data_full(file.first().unwrap_or(&DEFAULT_INPUT)) };
+    match args.as_slice() {
+        [] => {
+            let input = load(&[]);
+            println!("Day 07 Part 1: {}", part_1(&input, DEFAULT_TARGET)?);
+            println!("Day 07  Part 2: {}", part_2(&input, DEFAULT_TARGET)?);
+        }
+        ["containers-of", color, file @ ..] => {
+            let input = load(file);
+            let graph = BagGraph::parse(&input)?;
+            let containers = graph.ancestors(graph.id(color)?);
+            for &bag in &containers {
+                println!("{}", graph.name(bag));
+            }
+            println!("{} bags can contain {} bags", containers.len(), color);
+        }
+        ["contents-of", color, file @ ..] => {
+            let input = load(file);
+            let graph = BagGraph::parse(&input)?;
+            let bag = graph.id(color)?;
+            let total = graph.count_contents(bag)?;
+            for (content, quantity) in graph.content_quantities(bag)? {
+                println!("{:>8} {}", quantity, graph.name(content));
+            }
+            println!("{} bags inside {} bags", total, color);
+        }
+        ["path", from, to, file @ ..] => {
+            let input = load(file);
+            let graph = BagGraph::parse(&input)?;
+            let path = graph
+                .path(graph.id(from)?, graph.id(to)?)
+                .ok_or_else(|| anyhow!("{} bags cannot contain {} bags", from, to))?;
+            println!("{}", graph.format_path(graph.id(from)?, &path)?);
+        }
+        ["depth", color, file @ ..] => {
+            let input = load(file);
+            let graph = BagGraph::parse(&input)?;
+            println!("{}", graph.depth(graph.id(color)?)?);
+        }
+        ["export", options @ ..] => export(options)?,
+        _ => return Err(anyhow!("Unknown command {}", args.join(" "))),
     }
-    let captures: Captures = RE.captures(input).ok_or(anyhow!("Not a valid rule"))?;
-    let color = parse_color(captures.get(1).unwrap().as_str())?;
-    let contents = captures.get(2).unwrap().as_str();
-    if contents == "no other bags" {
-        return Ok((color, vec![]));
-    };
-    let contents: Result<Vec<_>> = contents
-        .split(", ")
-        .map(|desc| {
-            let desc = CONTENT
-                .captures(desc)
-                .ok_or(anyhow!("Invalid description"))?;
-            Ok((
-                desc.get(1).unwrap().as_str().parse().unwrap(),
-                parse_color(desc.get(2).unwrap().as_str())?,
-            ))
-        })
-        .collect();
-    Ok((color, contents?))
+    Ok(())
 }
 
-fn parse_rules(input: &str) -> Result<HashMap<&str, Vec<(usize, &str)>>> {
-    input.lines().map(|line| parse_rule(line)).collect()
-}
-
-fn part_1(input: &str) -> usize {
-    let mut parsed = parse_rules(input).unwrap();
-    let mut valid = HashSet::new();
-    valid.insert("shiny gold");
-
+fn export(mut options: &[&str]) -> Result<()> {
+    let mut format = Format::Dot;
+    let mut scope = None;
+    let mut highlight = None;
+    let mut file = DEFAULT_INPUT;
     loop {
-        let len_before = valid.len();
-        parsed = parsed
-            .into_iter()
-            .filter(|(bag, contents)| {
-                if contents
-                    .into_iter()
-                    .any(|&(_, color)| valid.contains(color))
-                {
-                    valid.insert(*bag);
-                    return false;
-                };
-                true
-            })
-            .collect();
-
-        if len_before == valid.len() {
-            break;
+        match options {
+            ["--format", value, rest @ ..] => {
+                format = value.parse().map_err(|e| anyhow!("{}", e))?;
+                options = rest;
+            }
+            [option @ "--ancestors", color, rest @ ..]
+            | [option @ "--descendants", color, rest @ ..] => {
+                scope = Some((*option, *color));
+                options = rest;
+            }
+            ["--highlight", color, rest @ ..] => {
+                highlight = Some(*color);
+                options = rest;
+            }
+            [input] if !input.starts_with("--") => {
+                file = input;
+                options = &[];
+            }
+            [] => break,
+            _ => return Err(anyhow!("Invalid export options {}", options.join(" "))),
         }
     }
 
-    valid.len() - 1
+    let input: String = load_data_full(file);
+    let graph = BagGraph::parse(&input)?;
+    let scope = match scope {
+        Some(("--ancestors", color)) => Scope::Ancestors(graph.id(color)?),
+        Some((_, color)) => Scope::Descendants(graph.id(color)?),
+        None => Scope::All,
+    };
+    let mut writer = GraphWriter::new(format, scope);
+    if let Some(color) = highlight {
+        writer.highlight = Some(graph.id(color)?);
+    }
+    print!("{}", writer.write(&graph));
+    Ok(())
+}
+
+fn part_1(input: &str, color: &str) -> Result<usize> {
+    let graph = BagGraph::parse(input)?;
+    Ok(graph.ancestors(graph.id(color)?).len())
 }
 
-fn count_recursive(color: &str, rules: &HashMap<&str, Vec<(usize, &str)>>) -> Result<usize> {
-    let rule = rules.get(color).ok_or(anyhow!("Rule does not exist"))?;
-    rule.into_iter()
-        .map(|&(count, name)| Ok(count + count * count_recursive(name, rules)?))
-        .sum()
+fn count_contents(color: &str, graph: &BagGraph) -> Result<usize> {
+    Ok(graph.count_contents(graph.id(color)?)?)
 }
 
-fn part_2(input: &str) -> usize {
-    let rules = parse_rules(input).unwrap();
-    count_recursive("shiny gold", &rules).unwrap()
+fn part_2(input: &str, color: &str) -> Result<usize> {
+    let graph = BagGraph::parse(input)?;
+    count_contents(color, &graph)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use day_07::parse_rule;
 
     fn get_input() -> String {
         let data = "\
@@ -125,7 +155,7 @@ dark violet bags contain no other bags.";
         let parsed = input
             .lines()
             .map(|line| parse_rule(line))
-            .collect::<Result<Vec<_>>>()
+            .collect::<Result<Vec<_>, _>>()
             .unwrap();
         assert_eq!(
             parsed,
@@ -149,20 +179,23 @@ dark violet bags contain no other bags.";
     #[test]
     fn test_part_1() {
         let input = get_input();
-        assert_eq!(part_1(&input), 4)
+        assert_eq!(part_1(&input, "shiny gold").unwrap(), 4);
+        assert_eq!(part_1(&input, "bright white").unwrap(), 2);
+        assert!(part_1(&input, "plaid purple").is_err());
     }
 
     #[test]
     fn test_part_2() {
         let input = get_input();
         assert_eq!(
-            count_recursive("shiny gold", &parse_rules(&input).unwrap()).unwrap(),
+            count_contents("shiny gold", &BagGraph::parse(&input).unwrap()).unwrap(),
             32
         );
         let input_2 = get_input_2();
         assert_eq!(
-            count_recursive("shiny gold", &parse_rules(&input_2).unwrap()).unwrap(),
+            count_contents("shiny gold", &BagGraph::parse(&input_2).unwrap()).unwrap(),
             126
         );
+        assert_eq!(part_2(&input_2, "dark green").unwrap(), 6);
     }
 }